@@ -0,0 +1,452 @@
+//! Page bridge (Windows only): messages between the wrapped page and the wrapper over
+//! WebView2's `chrome.webview.postMessage`. Unlike Tauri IPC it needs no capability,
+//! so it works for remote pages. The page posts objects `{ wrapper: "<action>" }`;
+//! wry only handles string messages, so the two don't interfere. Messages to the page
+//! have the same shape (`{ wrapper: "<event>", ... }`) and arrive as `message` events
+//! on `chrome.webview`.
+
+use crate::config::AppConfig;
+use tauri::Manager;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use webview2_com::Microsoft::Web::WebView2::Win32::{
+    ICoreWebView2, ICoreWebView2WebMessageReceivedEventArgs,
+};
+use windows::core::HSTRING;
+
+/// Frameless window dragging: a mouse press on an element marked `data-wrapper-drag`
+/// (with `regions`) or within the top `caption` CSS px — and not on a control, or
+/// anything marked `data-wrapper-no-drag` — hands the drag to Windows, so it behaves
+/// like a title bar, including Aero Snap. A double click toggles maximize unless
+/// `doubleClick` is off.
+const DRAG_SCRIPT: &str = r#"(() => {
+  if (window.top !== window) return;
+  const { regions, caption, doubleClick } = {options};
+  const post = (action) => window.chrome?.webview?.postMessage({ wrapper: action });
+  const NO_DRAG = '[data-wrapper-no-drag], a, button, input, select, textarea, [contenteditable]';
+  const inDragRegion = (e) => {
+    const el = e.target instanceof Element ? e.target : null;
+    if (!el || el.closest(NO_DRAG)) return false;
+    return e.clientY < caption || (regions && !!el.closest('[data-wrapper-drag]'));
+  };
+  addEventListener('mousedown', (e) => {
+    if (e.button !== 0 || !inDragRegion(e)) return;
+    e.preventDefault();
+    post(e.detail === 2 && doubleClick ? 'toggle-maximize' : 'drag');
+  }, true);
+})();"#;
+
+/// Kiosk exit gesture: holding a finger (or the mouse) on one screen corner for
+/// `{hold_ms}` without moving away posts `exit-gesture`. The corner zone is 60 CSS px.
+const EXIT_GESTURE_SCRIPT: &str = r#"(() => {
+  if (window.top !== window) return;
+  const ZONE = 60, RIGHT = {right}, BOTTOM = {bottom}, HOLD_MS = {hold_ms};
+  const inCorner = (e) =>
+    (RIGHT ? e.clientX >= innerWidth - ZONE : e.clientX <= ZONE) &&
+    (BOTTOM ? e.clientY >= innerHeight - ZONE : e.clientY <= ZONE);
+  let timer = null;
+  const cancel = () => { clearTimeout(timer); timer = null; };
+  addEventListener('pointerdown', (e) => {
+    cancel();
+    if (!inCorner(e)) return;
+    timer = setTimeout(() => {
+      timer = null;
+      window.chrome?.webview?.postMessage({ wrapper: 'exit-gesture' });
+    }, HOLD_MS);
+  }, true);
+  addEventListener('pointermove', (e) => { if (timer && !inCorner(e)) cancel(); }, true);
+  addEventListener('pointerup', cancel, true);
+  addEventListener('pointercancel', cancel, true);
+})();"#;
+
+/// Freeze watchdog: answers the wrapper's `ping` with a `pong`. A page whose main
+/// thread is stuck can't run this, which is how the wrapper tells it has hung.
+const WATCHDOG_SCRIPT: &str = r#"(() => {
+  if (window.top !== window) return;
+  window.chrome?.webview?.addEventListener('message', (e) => {
+    if (e.data?.wrapper === 'ping') window.chrome.webview.postMessage({ wrapper: 'pong' });
+  });
+})();"#;
+
+/// Diagnostics overlay: Ctrl+Alt+D asks the wrapper for its state and shows the
+/// answer in a box over the page; the same keys close it again.
+const DIAGNOSTICS_SCRIPT: &str = r#"(() => {
+  if (window.top !== window) return;
+  const webview = window.chrome?.webview;
+  if (!webview) return;
+  let overlay = null;
+  addEventListener('keydown', (e) => {
+    if (!e.ctrlKey || !e.altKey || e.shiftKey || e.code !== 'KeyD') return;
+    e.preventDefault();
+    if (overlay) {
+      overlay.remove();
+      overlay = null;
+    } else {
+      webview.postMessage({ wrapper: 'get-diagnostics' });
+    }
+  }, true);
+  webview.addEventListener('message', (e) => {
+    if (e.data?.wrapper !== 'diagnostics') return;
+    overlay?.remove();
+    overlay = document.createElement('pre');
+    overlay.style.cssText = `position:fixed;left:12px;bottom:12px;z-index:2147483647;margin:0;
+      padding:10px 14px;max-width:calc(100vw - 24px);overflow:hidden;white-space:pre-wrap;
+      font:12px/1.5 Consolas,monospace;color:#e8e8e8;background:rgba(0,0,0,.85);
+      border-radius:6px;pointer-events:none`;
+    overlay.textContent = Object.entries(e.data.values)
+      .map(([key, value]) => `${key.padEnd(12)} ${value}`).join('\n');
+    document.documentElement.appendChild(overlay);
+  });
+})();"#;
+
+/// State shared between the watchdog thread and the page message handler
+#[derive(Default)]
+struct Watchdog {
+    /// A pong arrived since the last ping
+    answered: AtomicBool,
+    /// The current page has answered at least once, so its script is running.
+    /// Until then (page still loading) a missing pong isn't counted.
+    armed: AtomicBool,
+}
+
+/// Install the page scripts enabled in config and the handler for page messages
+pub unsafe fn register(core: &ICoreWebView2, window: tauri::WebviewWindow, config: &AppConfig) {
+    use webview2_com::WebMessageReceivedEventHandler;
+
+    let frameless_snap = config.frameless_snap();
+    let caption_height = config.caption_height();
+    let draggable = frameless_snap || caption_height.is_some();
+    let enable_bridge = config.enable_bridge.eq_ignore_ascii_case("on");
+    let exit_gesture = config.exit_gesture();
+    let freeze_timeout = config.freeze_timeout();
+    let alert_icon = config.resolve_alert_icon_path().and_then(|path| crate::load_icon(&path));
+    let diagnostics = config.diagnostics_enabled();
+    if !draggable
+        && !enable_bridge
+        && exit_gesture.is_none()
+        && freeze_timeout.is_none()
+        && alert_icon.is_none()
+        && !diagnostics
+    {
+        return;
+    }
+
+    if draggable {
+        let options = serde_json::json!({
+            "regions": frameless_snap,
+            "caption": caption_height.unwrap_or(0),
+            "doubleClick": config.titlebar_doubleclick(),
+        });
+        crate::webview::add_script(core, &DRAG_SCRIPT.replace("{options}", &options.to_string()));
+    }
+
+    if let Some(gesture) = &exit_gesture {
+        let script = EXIT_GESTURE_SCRIPT
+            .replace("{right}", &gesture.right.to_string())
+            .replace("{bottom}", &gesture.bottom.to_string())
+            .replace("{hold_ms}", &gesture.hold.as_millis().to_string());
+        crate::webview::add_script(core, &script);
+    }
+
+    if enable_bridge {
+        register_navigation_state(core);
+    }
+
+    let last_navigation = diagnostics.then(|| {
+        crate::webview::add_script(core, DIAGNOSTICS_SCRIPT);
+        track_navigation_status(core)
+    });
+    let instance_mode = match config.allow_only_one_instance.trim() {
+        "" => "off".to_string(),
+        mode => mode.to_lowercase(),
+    };
+
+    let watchdog = freeze_timeout.map(|timeout| {
+        crate::webview::add_script(core, WATCHDOG_SCRIPT);
+        start_watchdog(core, window.clone(), timeout)
+    });
+
+    let handler = WebMessageReceivedEventHandler::create(Box::new(move |webview, args| {
+        let (Some(webview), Some(args)) = (webview, args) else {
+            return Ok(());
+        };
+        let mut json = windows::core::PWSTR::null();
+        args.WebMessageAsJson(&mut json)?;
+        let json = webview2_com::take_pwstr(json);
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(&json) else {
+            return Ok(());
+        };
+
+        match message["wrapper"].as_str() {
+            Some("drag") if draggable => start_window_drag(&window),
+            Some("toggle-maximize") if draggable => {
+                if window.is_maximized().unwrap_or(false) {
+                    let _ = window.unmaximize();
+                } else {
+                    let _ = window.maximize();
+                }
+            }
+            Some("get-navigation-state") if enable_bridge => post_navigation_state(&webview),
+            // Only for the configured origin, like the diagnostics commands
+            Some("get-diagnostics") if message_from_own_origin(&window, &args) => {
+                if let Some(last_navigation) = &last_navigation {
+                    let status = last_navigation.lock().map(|s| s.clone()).unwrap_or_default();
+                    post_diagnostics(&window, &instance_mode, status);
+                }
+            }
+            Some("exit-gesture")
+                if exit_gesture.is_some() && message_from_own_origin(&window, &args) =>
+            {
+                if crate::quit_allowed(&window.state::<AppConfig>()) {
+                    crate::logging::log("Exit gesture: closing");
+                    window.app_handle().exit(0);
+                }
+            }
+            // Unread indicator until the window is focused (restored by the focus handler)
+            Some("attention") => {
+                if let Some(icon) = alert_icon.clone() {
+                    if !window.is_focused().unwrap_or(false) {
+                        let _ = window.set_icon(icon);
+                    }
+                }
+            }
+            Some("pong") => {
+                if let Some(watchdog) = &watchdog {
+                    watchdog.answered.store(true, Ordering::Relaxed);
+                    watchdog.armed.store(true, Ordering::Relaxed);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_WebMessageReceived(&handler, &mut token);
+}
+
+/// Ping the page every `timeout` and reload it when an armed page misses a pong
+/// (`freeze_timeout_secs`). A new navigation disarms the watchdog until the new
+/// page answers, so slow page loads aren't mistaken for a hang.
+unsafe fn start_watchdog(
+    core: &ICoreWebView2,
+    window: tauri::WebviewWindow,
+    timeout: std::time::Duration,
+) -> Arc<Watchdog> {
+    use webview2_com::NavigationStartingEventHandler;
+
+    let watchdog = Arc::new(Watchdog::default());
+
+    let state = watchdog.clone();
+    let handler = NavigationStartingEventHandler::create(Box::new(move |_webview, _args| {
+        state.armed.store(false, Ordering::Relaxed);
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_NavigationStarting(&handler, &mut token);
+
+    let state = watchdog.clone();
+    std::thread::spawn(move || loop {
+        state.answered.store(false, Ordering::Relaxed);
+        let _ = window.with_webview(|webview| {
+            if let Ok(core) = webview.controller().CoreWebView2() {
+                post(&core, serde_json::json!({ "wrapper": "ping" }));
+            }
+        });
+        std::thread::sleep(timeout);
+
+        if state.armed.load(Ordering::Relaxed) && !state.answered.load(Ordering::Relaxed) {
+            crate::logging::log(&format!(
+                "Page did not respond within {} s: reloading",
+                timeout.as_secs()
+            ));
+            state.armed.store(false, Ordering::Relaxed);
+            let _ = window.with_webview(|webview| {
+                if let Ok(core) = webview.controller().CoreWebView2() {
+                    let _ = core.Reload();
+                }
+            });
+        }
+    });
+
+    watchdog
+}
+
+/// Keep a short description of how the latest navigation ended, for the diagnostics
+/// overlay: `OK (HTTP 200)`, `HTTP 404` or `network error <n>`.
+unsafe fn track_navigation_status(core: &ICoreWebView2) -> Arc<Mutex<String>> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2NavigationCompletedEventArgs2, COREWEBVIEW2_WEB_ERROR_STATUS,
+    };
+    use webview2_com::NavigationCompletedEventHandler;
+    use windows::core::Interface;
+
+    let last_navigation = Arc::new(Mutex::new("none yet".to_string()));
+
+    let state = last_navigation.clone();
+    let handler = NavigationCompletedEventHandler::create(Box::new(move |_webview, args| {
+        let Some(args) = args else { return Ok(()) };
+        let mut success = windows::core::BOOL::default();
+        args.IsSuccess(&mut success)?;
+        let mut status: i32 = 0;
+        if let Ok(args) = args.cast::<ICoreWebView2NavigationCompletedEventArgs2>() {
+            args.HttpStatusCode(&mut status)?;
+        }
+
+        let description = if success.as_bool() && status < 400 {
+            format!("OK (HTTP {})", status)
+        } else if status >= 400 {
+            format!("HTTP {}", status)
+        } else {
+            let mut error = COREWEBVIEW2_WEB_ERROR_STATUS::default();
+            args.WebErrorStatus(&mut error)?;
+            format!("network error {}", error.0)
+        };
+        if let Ok(mut last) = state.lock() {
+            *last = description;
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_NavigationCompleted(&handler, &mut token);
+
+    last_navigation
+}
+
+/// Whether a web message was posted by a page on the configured URL's origin
+unsafe fn message_from_own_origin(
+    window: &tauri::WebviewWindow,
+    args: &ICoreWebView2WebMessageReceivedEventArgs,
+) -> bool {
+    let mut source = windows::core::PWSTR::null();
+    if args.Source(&mut source).is_err() {
+        return false;
+    }
+    let source = webview2_com::take_pwstr(source);
+    window.state::<AppConfig>().is_own_origin(&source)
+}
+
+/// Send the wrapper's current state to the diagnostics overlay. The zoom factor lives
+/// on the controller, so the message is built inside `with_webview`.
+fn post_diagnostics(window: &tauri::WebviewWindow, instance_mode: &str, last_navigation: String) {
+    let geometry = match (window.outer_position(), window.outer_size()) {
+        (Ok(position), Ok(size)) => format!(
+            "{}x{} at {},{}{}",
+            size.width,
+            size.height,
+            position.x,
+            position.y,
+            if window.is_maximized().unwrap_or(false) { " (maximized)" } else { "" }
+        ),
+        _ => "unknown".to_string(),
+    };
+    let process = match crate::SUPERVISOR_PID.get() {
+        Some(supervisor) => format!("{} (supervised by {})", std::process::id(), supervisor),
+        None => std::process::id().to_string(),
+    };
+    let config_path = if AppConfig::config_is_inline() {
+        "inline (environment or stdin)".to_string()
+    } else {
+        AppConfig::find_config_path()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| "not found".to_string())
+    };
+    let instance_mode = instance_mode.to_string();
+    let failover = crate::webview::FAILOVER_TARGET.lock().ok().and_then(|t| t.clone());
+
+    let _ = window.with_webview(move |webview| unsafe {
+        let controller = webview.controller();
+        let Ok(core) = controller.CoreWebView2() else { return };
+        let mut zoom: f64 = 1.0;
+        let _ = controller.ZoomFactor(&mut zoom);
+
+        let mut values = serde_json::json!({
+            "URL": crate::webview::current_source(&core),
+            "Window": geometry,
+            "Zoom": format!("{:.0}%", zoom * 100.0),
+            "Instances": instance_mode,
+            "PID": process,
+            "Config": config_path,
+            "Last load": last_navigation,
+        });
+        if let Some(failover) = failover {
+            values["Failover"] = failover.into();
+        }
+        post(&core, serde_json::json!({ "wrapper": "diagnostics", "values": values }));
+    });
+}
+
+/// Tell the page whether back/forward are available (for its own navigation buttons)
+/// whenever the history changes and after every navigation:
+/// `{ wrapper: "navigation-state", canGoBack, canGoForward, url }`.
+/// The page can also ask for it with `{ wrapper: "get-navigation-state" }`.
+unsafe fn register_navigation_state(core: &ICoreWebView2) {
+    use webview2_com::{HistoryChangedEventHandler, NavigationCompletedEventHandler};
+
+    let handler = HistoryChangedEventHandler::create(Box::new(|webview, _args| {
+        if let Some(webview) = webview {
+            post_navigation_state(&webview);
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_HistoryChanged(&handler, &mut token);
+
+    let handler = NavigationCompletedEventHandler::create(Box::new(|webview, _args| {
+        if let Some(webview) = webview {
+            post_navigation_state(&webview);
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_NavigationCompleted(&handler, &mut token);
+}
+
+unsafe fn post_navigation_state(core: &ICoreWebView2) {
+    let mut can_go_back = windows::core::BOOL::default();
+    let mut can_go_forward = windows::core::BOOL::default();
+    let _ = core.CanGoBack(&mut can_go_back);
+    let _ = core.CanGoForward(&mut can_go_forward);
+
+    post(
+        core,
+        serde_json::json!({
+            "wrapper": "navigation-state",
+            "canGoBack": can_go_back.as_bool(),
+            "canGoForward": can_go_forward.as_bool(),
+            "url": crate::webview::current_source(core),
+        }),
+    );
+}
+
+/// Send a message to the page
+unsafe fn post(core: &ICoreWebView2, message: serde_json::Value) {
+    let _ = core.PostWebMessageAsJson(&HSTRING::from(message.to_string()));
+}
+
+/// Start Windows' own title-bar drag loop at the cursor, as if the caption was pressed.
+/// That gives snapping to screen edges and drag-to-maximize for free.
+fn start_window_drag(window: &tauri::WebviewWindow) {
+    use windows::Win32::Foundation::{HWND, LPARAM, POINT, WPARAM};
+    use windows::Win32::UI::Input::KeyboardAndMouse::ReleaseCapture;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetCursorPos, PostMessageW, HTCAPTION, WM_NCLBUTTONDOWN,
+    };
+
+    let Ok(hwnd) = window.hwnd() else { return };
+    let hwnd = HWND(hwnd.0 as *mut _);
+
+    unsafe {
+        let mut point = POINT::default();
+        let _ = GetCursorPos(&mut point);
+        // WM_NCLBUTTONDOWN carries screen coordinates packed as two 16-bit values
+        let position = ((point.y as u16 as u32) << 16) | point.x as u16 as u32;
+        let _ = ReleaseCapture();
+        let _ = PostMessageW(
+            Some(hwnd),
+            WM_NCLBUTTONDOWN,
+            WPARAM(HTCAPTION as usize),
+            LPARAM(position as isize),
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Bundled web app (`bundle_dir`): static files from a local folder served through the
+//! `app` custom protocol, so the wrapper can run an offline SPA instead of a remote site.
+//! WebView2 reaches custom protocols as `http://<scheme>.localhost/`, other platforms as
+//! `<scheme>://localhost/`.
+
+use std::path::{Component, Path, PathBuf};
+use tauri::http::{header::CONTENT_TYPE, Request, Response, StatusCode};
+
+/// Name of the custom protocol serving `bundle_dir`
+pub const SCHEME: &str = "app";
+
+/// Start page when `bundle_dir` is set and `url` is left empty
+#[cfg(target_os = "windows")]
+pub const START_URL: &str = "http://app.localhost/index.html";
+#[cfg(not(target_os = "windows"))]
+pub const START_URL: &str = "app://localhost/index.html";
+
+/// `app://localhost/...` as the platform reaches it (unchanged outside Windows)
+pub fn platform_url(url: &str) -> String {
+    match url.strip_prefix("app://localhost") {
+        Some(rest) if cfg!(target_os = "windows") => format!("http://app.localhost{}", rest),
+        _ => url.to_string(),
+    }
+}
+
+/// Answer a request for a file under `root`. Directories serve their `index.html`.
+/// A path with no file extension that doesn't exist also gets the root `index.html`,
+/// so client-side routes of a single-page app survive a reload.
+pub fn respond(root: &Path, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let path = percent_decode(request.uri().path());
+    let Some(mut file) = resolve(root, &path) else {
+        return status(StatusCode::FORBIDDEN);
+    };
+    if file.is_dir() {
+        file.push("index.html");
+    }
+    if !file.is_file() && file.extension().is_none() {
+        file = root.join("index.html");
+    }
+
+    match std::fs::read(&file) {
+        Ok(body) => Response::builder()
+            .header(CONTENT_TYPE, mime_type(&file))
+            .body(body)
+            .unwrap_or_else(|_| status(StatusCode::INTERNAL_SERVER_ERROR)),
+        Err(_) => status(StatusCode::NOT_FOUND),
+    }
+}
+
+/// `path` (from the URL) under `root`. None when it tries to climb out of `root`.
+fn resolve(root: &Path, path: &str) -> Option<PathBuf> {
+    let mut file = root.to_path_buf();
+    for component in Path::new(path.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(part) => file.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(file)
+}
+
+fn status(code: StatusCode) -> Response<Vec<u8>> {
+    let mut response = Response::new(code.canonical_reason().unwrap_or_default().into());
+    *response.status_mut() = code;
+    response
+}
+
+/// Decode `%XX` escapes in a URL path. Invalid escapes are kept as they are.
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(byte) if bytes[i] == b'%' => {
+                decoded.push(byte);
+                i += 3;
+            }
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Content-Type for a file, by extension
+fn mime_type(file: &Path) -> &'static str {
+    let extension = file.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    match extension.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "json" | "map" => "application/json",
+        "webmanifest" => "application/manifest+json",
+        "txt" => "text/plain; charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "wasm" => "application/wasm",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn served(path: &str) -> Option<PathBuf> {
+        resolve(Path::new("site"), &percent_decode(path))
+    }
+
+    fn site(file: &str) -> Option<PathBuf> {
+        Some(Path::new("site").join(file))
+    }
+
+    #[test]
+    fn leading_slash_is_relative_to_the_root() {
+        assert_eq!(served("/"), site(""));
+        assert_eq!(served("/css/app.css"), site("css/app.css"));
+        assert_eq!(served("//server/share/a.js"), site("server/share/a.js"));
+    }
+
+    #[test]
+    fn parent_directories_are_refused() {
+        assert_eq!(served("/../secret.txt"), None);
+        assert_eq!(served("/css/../../secret.txt"), None);
+        assert_eq!(served("/%2e%2e/secret.txt"), None);
+        assert_eq!(served("/css/%2E%2E/%2e%2e/secret.txt"), None);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn backslashes_and_prefixes_are_refused() {
+        assert_eq!(served("/%5c..%5csecret.txt"), None);
+        assert_eq!(served("/C:/Windows/win.ini"), None);
+        assert_eq!(served("/%5c%5cserver%5cshare%5ca.js"), None);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn backslashes_and_prefixes_stay_file_names() {
+        // Only Windows treats `\` as a separator and `C:` as a drive
+        assert_eq!(served("/%5c..%5csecret.txt"), site("\\..\\secret.txt"));
+        assert_eq!(served("/C:/Windows/win.ini"), site("C:/Windows/win.ini"));
+    }
+
+    #[test]
+    fn invalid_escapes_are_kept() {
+        assert_eq!(percent_decode("/a%zzb"), "/a%zzb");
+        assert_eq!(percent_decode("/a%2"), "/a%2");
+        assert_eq!(percent_decode("/%41%20b"), "/A b");
+        assert_eq!(served("/%zz.."), site("%zz.."));
+    }
+}
@@ -0,0 +1,204 @@
+//! Tauri commands callable from the wrapped page via
+//! `window.__TAURI_INTERNALS__.invoke(<name>)`. Each command checks the config flag
+//! that enables it, since they share one `invoke_handler`.
+
+use crate::config::AppConfig;
+use serde_json::Value;
+use tauri::State;
+
+/// Keys with any of these as a whole `_`/`-`/`.`-separated word (case-insensitive) are
+/// treated as secrets and redacted, at any depth: `proxy_password`, but not `pinned`
+const SECRET_KEY_WORDS: &[&str] = &[
+    "password",
+    "passwd",
+    "passphrase",
+    "secret",
+    "token",
+    "cookie",
+    "cookies",
+    "credential",
+    "credentials",
+    "authorization",
+    "apikey",
+    "pin",
+];
+
+/// Query parameters redacted from URLs besides those matching `SECRET_KEY_WORDS`
+const SECRET_QUERY_PARAMS: &[&str] = &["key", "sig", "signature", "auth", "code"];
+
+const REDACTED: &str = "<redacted>";
+
+/// The config the wrapper is running with, as JSON, with secrets redacted.
+/// Requires `"diagnostics": "on"`.
+#[tauri::command]
+pub fn get_effective_config(config: State<'_, AppConfig>) -> Result<serde_json::Value, String> {
+    if !config.diagnostics_enabled() {
+        return Err("diagnostics are disabled".into());
+    }
+
+    let mut value = serde_json::to_value(config.inner()).map_err(|e| e.to_string())?;
+    redact(&mut value);
+    Ok(value)
+}
+
+/// Redact secrets throughout a JSON value: the values of secret-looking keys, and the
+/// credentials and token parameters of URLs inside strings (`url`, `urls`,
+/// `--proxy-server=...` in `extra_browser_args`, ...)
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if is_secret_key(key) && !field.is_null() {
+                    *field = Value::String(REDACTED.into());
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        Value::String(text) => *text = redact_urls(text),
+        _ => {}
+    }
+}
+
+fn is_secret_key(key: &str) -> bool {
+    key.split(['_', '-', '.'])
+        .any(|word| SECRET_KEY_WORDS.iter().any(|secret| word.eq_ignore_ascii_case(secret)))
+}
+
+/// Strip user info and secret query parameters from every URL in a space-separated
+/// string. A URL may follow `=` or a quote, as in `--proxy-server=http://user:pw@host`.
+fn redact_urls(text: &str) -> String {
+    if !text.contains("://") {
+        return text.to_string();
+    }
+    text.split(' ')
+        .map(|word| {
+            let Some(scheme_end) = word.find("://") else {
+                return word.to_string();
+            };
+            let start = word[..scheme_end].rfind(['=', '"', '\'']).map_or(0, |i| i + 1);
+            match redact_url(&word[start..]) {
+                Some(url) => format!("{}{}", &word[..start], url),
+                None => word.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The URL with its secrets removed, or None when it has none (so it is kept verbatim)
+fn redact_url(text: &str) -> Option<String> {
+    let mut url: tauri::Url = text.parse().ok()?;
+    let mut changed = false;
+    if !url.username().is_empty() || url.password().is_some() {
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+        changed = true;
+    }
+
+    let is_secret_param = |name: &str| {
+        is_secret_key(name) || SECRET_QUERY_PARAMS.iter().any(|p| name.eq_ignore_ascii_case(p))
+    };
+    let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+    if pairs.iter().any(|(name, _)| is_secret_param(name)) {
+        url.query_pairs_mut().clear().extend_pairs(pairs.iter().map(|(name, value)| {
+            let value = if is_secret_param(name) { "REDACTED" } else { value.as_str() };
+            (name.as_str(), value)
+        }));
+        changed = true;
+    }
+    changed.then(|| url.to_string())
+}
+
+/// Navigate the window to an http(s) URL, e.g. from a page-drawn address bar.
+/// Requires `"navigation_commands": "on"`.
+#[tauri::command]
+pub fn navigate(
+    window: tauri::WebviewWindow,
+    config: State<'_, AppConfig>,
+    url: String,
+) -> Result<(), String> {
+    require_navigation_commands(&config)?;
+    let url: tauri::Url = url.trim().parse().map_err(|e| format!("invalid URL: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("unsupported URL scheme: {}", url.scheme()));
+    }
+    window.navigate(url).map_err(|e| e.to_string())
+}
+
+/// Reload the current page. Requires `"navigation_commands": "on"`.
+#[tauri::command]
+pub fn reload(window: tauri::WebviewWindow, config: State<'_, AppConfig>) -> Result<(), String> {
+    require_navigation_commands(&config)?;
+    window.reload().map_err(|e| e.to_string())
+}
+
+/// Go back in history. Requires `"navigation_commands": "on"`.
+#[tauri::command]
+pub fn go_back(window: tauri::WebviewWindow, config: State<'_, AppConfig>) -> Result<(), String> {
+    require_navigation_commands(&config)?;
+    window.eval("history.back()").map_err(|e| e.to_string())
+}
+
+/// Go forward in history. Requires `"navigation_commands": "on"`.
+#[tauri::command]
+pub fn go_forward(window: tauri::WebviewWindow, config: State<'_, AppConfig>) -> Result<(), String> {
+    require_navigation_commands(&config)?;
+    window.eval("history.forward()").map_err(|e| e.to_string())
+}
+
+fn require_navigation_commands(config: &AppConfig) -> Result<(), String> {
+    if config.navigation_commands_enabled() {
+        Ok(())
+    } else {
+        Err("navigation commands are disabled".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn secret_keys_match_whole_words() {
+        assert!(is_secret_key("proxy_password"));
+        assert!(is_secret_key("Api-Token"));
+        assert!(is_secret_key("pin"));
+        assert!(!is_secret_key("pinned_tabs"));
+        assert!(!is_secret_key("integrated_auth"));
+        assert!(!is_secret_key("spinner"));
+    }
+
+    #[test]
+    fn redacts_nested_keys() {
+        let mut value = json!({ "headers": { "X-Auth-Token": "abc" }, "pinned": "on" });
+        redact(&mut value);
+        assert_eq!(value, json!({ "headers": { "X-Auth-Token": REDACTED }, "pinned": "on" }));
+    }
+
+    #[test]
+    fn strips_url_credentials_and_tokens() {
+        let mut value = json!({
+            "url": "https://user:pw@example.com/app?token=abc&page=2",
+            "urls": ["https://backup.example.com/?key=xyz"],
+            "fallback_url": "https://example.com/down.html",
+        });
+        redact(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "url": "https://example.com/app?token=REDACTED&page=2",
+                "urls": ["https://backup.example.com/?key=REDACTED"],
+                "fallback_url": "https://example.com/down.html",
+            })
+        );
+    }
+
+    #[test]
+    fn strips_proxy_credentials_from_browser_args() {
+        let args = "--proxy-server=http://user:pw@proxy:8080 --disable-gpu";
+        assert_eq!(redact_urls(args), "--proxy-server=http://proxy:8080/ --disable-gpu");
+    }
+}
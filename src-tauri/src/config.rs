@@ -1,24 +1,238 @@
+use crate::logging;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct AppConfig {
+    #[serde(default)]
     pub url: String,
     #[serde(default)]
+    pub bundle_dir: String,
+    #[serde(default)]
     pub title: String,
     #[serde(default)]
+    pub title_template: String,
+    #[serde(default)]
+    pub taskbar_title: String,
+    #[serde(default)]
     pub icon: String,
     #[serde(default)]
+    pub alert_icon: String,
+    #[serde(default)]
     pub prefer_dark_mode: String,
     #[serde(default)]
     pub force_dark_mode: String,
     #[serde(default)]
+    pub zoom_locked: String,
+    #[serde(default)]
+    pub zoom_per_site: String,
+    #[serde(default)]
+    pub default_zoom: Option<f64>,
+    #[serde(default)]
+    pub use_theme_color: String,
+    #[serde(default)]
+    pub accessibility: String,
+    #[serde(default)]
+    pub font_scale: Option<f64>,
+    #[serde(default)]
+    pub autofill: String,
+    #[serde(default)]
+    pub statusbar: String,
+    #[serde(default)]
+    pub permission_prompts: String,
+    #[serde(default)]
+    pub script_dialogs: String,
+    #[serde(default)]
+    pub downloads: String,
+    #[serde(default)]
+    pub unattended: String,
+    #[serde(default)]
+    pub enable_fullscreen_key: String,
+    #[serde(default)]
+    pub load_images: String,
+    #[serde(default)]
+    pub block_hosts: Vec<String>,
+    #[serde(default)]
+    pub referer: HashMap<String, String>,
+    #[serde(default)]
+    pub autoplay_hosts: Vec<String>,
+    #[serde(default)]
+    pub host_header: String,
+    #[serde(default)]
+    pub cookies_file: String,
+    #[serde(default)]
+    pub integrated_auth: String,
+    #[serde(default)]
+    pub locale: String,
+    #[serde(default)]
+    pub local_storage: HashMap<String, String>,
+    #[serde(default)]
+    pub session_storage: HashMap<String, String>,
+    #[serde(default)]
+    pub loading_overlay: String,
+    #[serde(default)]
+    pub loading_overlay_color: String,
+    #[serde(default)]
+    pub loading_overlay_logo: String,
+    #[serde(default)]
+    pub splash_bg: String,
+    #[serde(default)]
+    pub content_aspect: String,
+    #[serde(default)]
+    pub treat_http_errors: String,
+    #[serde(default)]
+    pub min_tls: String,
+    #[serde(default)]
+    pub navigation_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub loading_timeout_action: String,
+    #[serde(default)]
+    pub fallback_url: String,
+    #[serde(default)]
+    pub clear_cache_on_update: String,
+    #[serde(default)]
+    pub content_version: String,
+    #[serde(default)]
+    pub urls: Vec<String>,
+    #[serde(default)]
+    pub connection_banner: String,
+    #[serde(default)]
+    pub freeze_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub min_uptime_secs: Option<u64>,
+    #[serde(default)]
+    pub max_memory_mb: Option<u64>,
+    #[serde(default)]
+    pub process_priority: String,
+    #[serde(default)]
+    pub throttle_background: String,
+    #[serde(default)]
+    pub resume_last_url: String,
+    #[serde(default)]
+    pub recent_pages: String,
+    #[serde(default)]
+    pub reload_on_resume: String,
+    #[serde(default)]
+    pub reload_on_time_change: String,
+    #[serde(default)]
+    pub external_schemes: Option<Vec<String>>,
+    #[serde(default)]
+    pub decorations: String,
+    #[serde(default)]
+    pub always_on_top: String,
+    #[serde(default)]
+    pub skip_taskbar: String,
+    #[serde(default)]
+    pub transparent: String,
+    #[serde(default)]
+    pub resizable: String,
+    #[serde(default)]
+    pub hud: String,
+    #[serde(default)]
+    pub window_shadow: String,
+    #[serde(default)]
+    pub frameless_snap: String,
+    #[serde(default)]
+    pub app_region: String,
+    #[serde(default)]
+    pub caption_height: Option<u32>,
+    #[serde(default)]
+    pub titlebar_doubleclick: String,
+    #[serde(default)]
+    pub autohide_titlebar: String,
+    #[serde(default)]
+    pub enable_bridge: String,
+    #[serde(default)]
+    pub exit_gesture: String,
+    #[serde(default)]
+    pub shortcuts: HashMap<String, String>,
+    #[serde(default)]
+    pub clear_schedule: String,
+    #[serde(default)]
+    pub clear_data: Option<Vec<String>>,
+    #[serde(default)]
+    pub schedule: Vec<ScheduleEntry>,
+    #[serde(default)]
     pub start_minimized: String,
     #[serde(default)]
+    pub defer_show: String,
+    #[serde(default)]
+    pub start_hidden: String,
+    #[serde(default)]
+    pub tray_taskbar: String,
+    #[serde(default)]
+    pub defer_navigation: String,
+    #[serde(default)]
+    pub window_close_request: String,
+    #[serde(default)]
+    pub file_drop: String,
+    #[serde(default)]
     pub allow_only_one_instance: String,
+    #[serde(default)]
+    pub kill_wait_ms: Option<u64>,
+    #[serde(default)]
+    pub single_window_per_url: String,
+    #[serde(default)]
+    pub app_user_model_id: String,
+    #[serde(default)]
+    pub window_class: String,
+    #[serde(default)]
+    pub jump_list: String,
+    #[serde(default)]
+    pub system_menu: String,
+    #[serde(default)]
+    pub protocol_scheme: String,
+    #[serde(default)]
+    pub restore_maximized_as_normal: String,
+    #[serde(default)]
+    pub maximize_save_delay_ms: Option<u64>,
+    #[serde(default)]
+    pub min_restore_size: Option<u32>,
+    #[serde(default)]
+    pub remember_fullscreen: String,
+    #[serde(default)]
+    pub cascade_step_x: Option<i32>,
+    #[serde(default)]
+    pub cascade_step_y: Option<i32>,
+    #[serde(default)]
+    pub geometry: String,
+    #[serde(default)]
+    pub layouts: HashMap<String, Layout>,
+    #[serde(default)]
+    pub hardware_acceleration: String,
+    #[serde(default)]
+    pub extra_browser_args: String,
+    #[serde(default)]
+    pub user_data_folder: String,
+    #[serde(default)]
+    pub relaunch_on_config_change: String,
+    #[serde(default)]
+    pub logging: String,
+    #[serde(default)]
+    pub navigation_commands: String,
+    #[serde(default)]
+    pub diagnostics: String,
+    #[serde(default)]
+    pub devtools: String,
+    #[serde(default)]
+    pub open_devtools: String,
+    #[serde(default)]
+    pub portable: String,
 }
 
-/// Persisted window geometry — saved beside the config as `<name>.window.json`
+/// Number of pages `recent_pages` keeps
+pub const MAX_HISTORY: usize = 10;
+
+/// Set once a loaded config has `"portable": "on"` (see `AppConfig::is_portable`)
+static PORTABLE: AtomicBool = AtomicBool::new(false);
+
+/// Slot this instance holds when several may run (see `AppConfig::set_instance_slot`)
+static INSTANCE_SLOT: AtomicU32 = AtomicU32::new(1);
+
+/// Persisted window geometry — saved beside the config as `<name>.window.json`.
+/// `x`/`y` and `width`/`height` are the inner (client-area) position and size.
 #[derive(Serialize, Deserialize, Default)]
 pub struct WindowState {
     pub x: i32,
@@ -26,77 +240,600 @@ pub struct WindowState {
     pub width: u32,
     pub height: u32,
     pub maximized: bool,
+    /// Monitor scale factor when saved (0 in files from older versions = unknown)
+    #[serde(default)]
+    pub scale_factor: f64,
+    /// Monitor the window was fullscreen on when saved (`remember_fullscreen`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fullscreen_monitor: Option<String>,
+}
+
+/// Named window arrangement from `layouts`, chosen at launch with `--layout <name>`.
+/// Size and position are in physical pixels; unset fields keep their default.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Layout {
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub x: Option<i32>,
+    #[serde(default)]
+    pub y: Option<i32>,
+    #[serde(default)]
+    pub maximized: bool,
+}
+
+/// One `schedule` entry: show `url` during `time` (`"HH:MM-HH:MM"`, local time; may run
+/// past midnight), on the given `days` (`"mon"`..`"sun"`, empty = every day)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScheduleEntry {
+    pub time: String,
+    pub url: String,
+    #[serde(default)]
+    pub days: Vec<String>,
+}
+
+/// Validated `schedule` entry. Times are minutes since midnight; `days` is a bit mask
+/// indexed by weekday (0 = Sunday, as in `SYSTEMTIME::wDayOfWeek`).
+pub struct ScheduleSlot {
+    start: u16,
+    end: u16,
+    days: u8,
+    pub url: tauri::Url,
+}
+
+impl ScheduleSlot {
+    fn parse(entry: &ScheduleEntry) -> Result<Self, String> {
+        let invalid = |what: &str| format!("schedule entry {:?}: invalid {}", entry.time, what);
+
+        let (start, end) = entry.time.split_once('-').ok_or_else(|| invalid("time"))?;
+        let start = parse_minute_of_day(start).filter(|&m| m < 24 * 60);
+        let end = parse_minute_of_day(end);
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(invalid("time (expected \"HH:MM-HH:MM\")"));
+        };
+        if start == end {
+            return Err(invalid("time (empty range)"));
+        }
+
+        let mut days = 0u8;
+        for day in &entry.days {
+            const NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+            let day = day.trim().to_ascii_lowercase();
+            let index = NAMES
+                .iter()
+                .position(|name| day.starts_with(name))
+                .ok_or_else(|| invalid(&format!("day {:?}", day)))?;
+            days |= 1 << index;
+        }
+
+        let url: tauri::Url = entry.url.parse().map_err(|_| invalid("url"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(invalid("url (http or https only)"));
+        }
+
+        Ok(ScheduleSlot {
+            start,
+            end,
+            days: if days == 0 { 0x7f } else { days },
+            url,
+        })
+    }
+
+    /// Whether the slot covers `minute` (since midnight) on `weekday` (0 = Sunday).
+    /// The part of an overnight slot after midnight belongs to the previous day.
+    pub fn is_active(&self, weekday: u16, minute: u16) -> bool {
+        let on = |weekday: u16| self.days & (1 << (weekday % 7)) != 0;
+        if self.start < self.end {
+            on(weekday) && (self.start..self.end).contains(&minute)
+        } else {
+            (on(weekday) && minute >= self.start) || (on(weekday + 6) && minute < self.end)
+        }
+    }
+}
+
+/// `"HH:MM"` as minutes since midnight; `"24:00"` is allowed as the end of a day
+fn parse_minute_of_day(time: &str) -> Option<u16> {
+    let (hour, minute) = time.trim().split_once(':')?;
+    // Checked before multiplying, so a stray "1100:00" can't overflow
+    let hour: u16 = hour.parse().ok().filter(|&h| h <= 24)?;
+    let minute: u16 = minute.parse().ok().filter(|&m| m < 60)?;
+    let total = hour * 60 + minute;
+    (total <= 24 * 60).then_some(total)
+}
+
+/// What happens to a page load that runs into the navigation timeout
+#[derive(Clone)]
+pub enum LoadingTimeoutAction {
+    /// The error page with a Retry button
+    ShowError,
+    /// Load the same page again
+    Retry,
+    /// Show `fallback_url`
+    Fallback(tauri::Url),
+}
+
+/// What a `shortcuts` key does
+#[derive(Clone, Copy, PartialEq)]
+pub enum ShortcutAction {
+    Reload,
+    HardReload,
+    Home,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    Fullscreen,
+    Quit,
+}
+
+impl ShortcutAction {
+    fn parse(action: &str) -> Option<Self> {
+        Some(match action.trim().to_ascii_lowercase().as_str() {
+            "reload" => ShortcutAction::Reload,
+            "hard-reload" => ShortcutAction::HardReload,
+            "home" | "navigate-home" => ShortcutAction::Home,
+            "zoom-in" => ShortcutAction::ZoomIn,
+            "zoom-out" => ShortcutAction::ZoomOut,
+            "zoom-reset" => ShortcutAction::ZoomReset,
+            "fullscreen" => ShortcutAction::Fullscreen,
+            "quit" => ShortcutAction::Quit,
+            _ => return None,
+        })
+    }
+}
+
+/// Validated `shortcuts` entry: modifiers plus a Windows virtual-key code
+#[derive(Clone)]
+pub struct Shortcut {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub key: u16,
+    pub action: ShortcutAction,
+}
+
+impl Shortcut {
+    /// Parse `"Ctrl+Shift+K"` → `"zoom-in"`. Modifiers come first, in any order.
+    fn parse(spec: &str, action: &str) -> Result<Self, String> {
+        let invalid = |what: &str| format!("shortcuts entry {:?}: {}", spec, what);
+        let action = ShortcutAction::parse(action)
+            .ok_or_else(|| invalid(&format!("unknown action {:?}", action)))?;
+
+        let mut shortcut = Shortcut { ctrl: false, shift: false, alt: false, key: 0, action };
+        let mut parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+        for modifier in parts {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => shortcut.ctrl = true,
+                "shift" => shortcut.shift = true,
+                "alt" => shortcut.alt = true,
+                _ => return Err(invalid(&format!("unknown modifier {:?}", modifier))),
+            }
+        }
+        shortcut.key = virtual_key(key).ok_or_else(|| invalid(&format!("unknown key {:?}", key)))?;
+        Ok(shortcut)
+    }
+}
+
+/// Windows virtual-key code for a key name: a letter, a digit, `F1`-`F24` or a named key
+fn virtual_key(name: &str) -> Option<u16> {
+    let upper = name.to_ascii_uppercase();
+    if let [c] = upper.as_bytes() {
+        return c.is_ascii_alphanumeric().then_some(*c as u16);
+    }
+    if let Some(n) = upper.strip_prefix('F').and_then(|n| n.parse::<u16>().ok()) {
+        return (1..=24).contains(&n).then_some(0x6F + n);
+    }
+    Some(match upper.as_str() {
+        "ESC" | "ESCAPE" => 0x1B,
+        "SPACE" => 0x20,
+        "ENTER" => 0x0D,
+        "TAB" => 0x09,
+        "BACKSPACE" => 0x08,
+        "DELETE" => 0x2E,
+        "INSERT" => 0x2D,
+        "HOME" => 0x24,
+        "END" => 0x23,
+        "PAGEUP" => 0x21,
+        "PAGEDOWN" => 0x22,
+        "LEFT" => 0x25,
+        "UP" => 0x26,
+        "RIGHT" => 0x27,
+        "DOWN" => 0x28,
+        "PLUS" => 0xBB,
+        "MINUS" => 0xBD,
+        _ => return None,
+    })
+}
+
+/// Parsed `exit_gesture`, e.g. `top-left-5s`: hold the given screen corner this long
+pub struct ExitGesture {
+    pub bottom: bool,
+    pub right: bool,
+    pub hold: std::time::Duration,
+}
+
+impl ExitGesture {
+    /// Parse `<top|bottom>-<left|right>-<seconds>s`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let spec = spec.trim().to_ascii_lowercase();
+        let mut parts = spec.split('-');
+        let bottom = match parts.next()? {
+            "top" => false,
+            "bottom" => true,
+            _ => return None,
+        };
+        let right = match parts.next()? {
+            "left" => false,
+            "right" => true,
+            _ => return None,
+        };
+        let secs: u64 = parts.next()?.strip_suffix('s')?.parse().ok()?;
+        if parts.next().is_some() || secs == 0 {
+            return None;
+        }
+        Some(ExitGesture {
+            bottom,
+            right,
+            hold: std::time::Duration::from_secs(secs),
+        })
+    }
+}
+
+/// Parsed `clear_schedule`: every N minutes (`"60"`) or daily at a local time (`"03:00"`)
+#[derive(Clone, Copy)]
+pub enum ClearSchedule {
+    Every(std::time::Duration),
+    Daily { hour: u16, minute: u16 },
+}
+
+impl ClearSchedule {
+    pub fn parse(spec: &str) -> Option<Self> {
+        let spec = spec.trim();
+        if let Some((hour, minute)) = spec.split_once(':') {
+            let hour: u16 = hour.parse().ok().filter(|&h| h < 24)?;
+            let minute: u16 = minute.parse().ok().filter(|&m| m < 60)?;
+            return Some(ClearSchedule::Daily { hour, minute });
+        }
+        let minutes: u64 = spec.strip_suffix('m').unwrap_or(spec).parse().ok()?;
+        (minutes > 0).then(|| ClearSchedule::Every(std::time::Duration::from_secs(minutes * 60)))
+    }
+}
+
+/// One dimension of a `geometry` spec: absolute pixels or a percentage of the monitor
+#[derive(Clone, Copy)]
+pub enum Extent {
+    Pixels(i32),
+    Percent(f64),
+}
+
+/// Parsed `geometry` config value, e.g. `monitor:2 50%x100% +0+0`.
+/// Size and offset are relative to the monitor's work area; monitors are numbered from 1.
+pub struct Geometry {
+    pub monitor: usize,
+    pub width: Extent,
+    pub height: Extent,
+    pub x: Extent,
+    pub y: Extent,
+}
+
+impl Extent {
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.strip_prefix('+').unwrap_or(s);
+        match s.strip_suffix('%') {
+            Some(pct) => pct.parse().ok().map(Extent::Percent),
+            None => s.parse().ok().map(Extent::Pixels),
+        }
+    }
+
+    /// Resolve against the monitor's extent along the same axis, in physical pixels
+    pub fn resolve(self, total: u32) -> i32 {
+        match self {
+            Extent::Pixels(px) => px,
+            Extent::Percent(pct) => (total as f64 * pct / 100.0).round() as i32,
+        }
+    }
+}
+
+impl Geometry {
+    /// Parse `[monitor:<n>] <w>x<h> [<+|-><x><+|-><y>]`. Each of w/h/x/y is either
+    /// pixels (`800`) or a percentage of the monitor (`50%`). Monitor defaults to 1,
+    /// offset defaults to `+0+0`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut geometry = Geometry {
+            monitor: 1,
+            width: Extent::Percent(100.0),
+            height: Extent::Percent(100.0),
+            x: Extent::Pixels(0),
+            y: Extent::Pixels(0),
+        };
+        let mut has_size = false;
+
+        for token in spec.split_whitespace() {
+            if let Some(index) = token.strip_prefix("monitor:") {
+                geometry.monitor = index.parse().ok().filter(|&n| n >= 1)?;
+            } else if token.starts_with('+') || token.starts_with('-') {
+                // Split "+X+Y" / "-X+Y" at the sign that starts the Y component
+                let split = token[1..].find(['+', '-'])? + 1;
+                geometry.x = Extent::parse(&token[..split])?;
+                geometry.y = Extent::parse(&token[split..])?;
+            } else {
+                let (w, h) = token.split_once(['x', 'X'])?;
+                geometry.width = Extent::parse(w)?;
+                geometry.height = Extent::parse(h)?;
+                has_size = true;
+            }
+        }
+
+        has_size.then_some(geometry)
+    }
 }
 
 impl AppConfig {
+    /// Config JSON comes from, in order: the `<EXE_NAME>_CONFIG_JSON` environment
+    /// variable, stdin (`--config -`), then the config file search.
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
-        let config_path = Self::find_config_path()?;
-        let contents = std::fs::read_to_string(&config_path)?;
-        let config: AppConfig = serde_json::from_str(&contents)?;
+        let contents = match Self::inline_config()? {
+            Some(json) => json,
+            None => std::fs::read_to_string(Self::find_config_path()?)?,
+        };
+        let mut config: AppConfig = serde_json::from_str(&contents)?;
+        if config.portable.eq_ignore_ascii_case("on") {
+            PORTABLE.store(true, Ordering::Relaxed);
+        }
+        // A bundled app starts at its index.html unless `url` says otherwise; a failover
+        // list alone starts at its first entry
+        if config.url.trim().is_empty() {
+            if let Some(first) = config.urls.first() {
+                config.url = first.clone();
+            } else if config.bundle_dir.trim().is_empty() {
+                return Err("missing field `url`".into());
+            } else {
+                config.url = crate::bundle::START_URL.to_string();
+            }
+        }
+        config.url = crate::bundle::platform_url(config.url.trim());
+        // Reject a broken schedule or shortcut map up front rather than silently showing
+        // the wrong page or ignoring a key
+        config.schedule()?;
+        config.shortcuts()?;
         Ok(config)
     }
 
-    pub(crate) fn config_filename() -> String {
-        // Derive config filename from the executable name: MyApp.exe -> MyApp.json
+    /// Name of the environment variable holding the whole config as JSON:
+    /// `MyApp.exe` -> `MYAPP_CONFIG_JSON`
+    pub(crate) fn config_env_var() -> String {
+        let name: String = Self::exe_stem()
+            .unwrap_or_else(|| "config".to_string())
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+            .collect();
+        format!("{}_CONFIG_JSON", name)
+    }
+
+    /// True when the config is given in the environment or on stdin rather than as a
+    /// file, so there is no file to watch for changes
+    pub(crate) fn config_is_inline() -> bool {
+        std::env::var_os(Self::config_env_var()).is_some()
+            || arg_value("--config").as_deref() == Some("-")
+    }
+
+    /// Config JSON from the environment variable or stdin, if one of them is used
+    fn inline_config() -> Result<Option<String>, Box<dyn std::error::Error>> {
+        if let Ok(json) = std::env::var(Self::config_env_var()) {
+            return Ok(Some(json));
+        }
+        if arg_value("--config").as_deref() == Some("-") {
+            let mut json = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut json)?;
+            return Ok(Some(json));
+        }
+        Ok(None)
+    }
+
+    /// Executable name without extension: `MyApp.exe` -> `MyApp`
+    pub(crate) fn exe_stem() -> Option<String> {
         std::env::current_exe()
             .ok()
             .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+    }
+
+    pub(crate) fn config_filename() -> String {
+        // Derive config filename from the executable name: MyApp.exe -> MyApp.json
+        Self::exe_stem()
             .map(|name| format!("{}.json", name))
             .unwrap_or_else(|| "config.json".to_string())
     }
 
-    fn find_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    pub(crate) fn find_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
         let config_name = Self::config_filename();
+        let exe_dir = Self::exe_dir().ok_or("Cannot determine exe directory")?;
+        let cwd = std::env::current_dir()?;
+
+        search_config_path(
+            &config_name,
+            config_override_arg().as_deref(),
+            &cwd,
+            Self::project_root().as_deref(),
+            &exe_dir,
+            |p| p.exists(),
+        )
+        .ok_or_else(|| format!("{} not found", config_name).into())
+    }
+
+    /// Directory containing the executable
+    fn exe_dir() -> Option<PathBuf> {
+        std::env::current_exe().ok()?.parent().map(Path::to_path_buf)
+    }
+
+    /// Portable mode (`--portable` or `"portable": "on"`): config, state, logs and the
+    /// WebView2 profile all live beside the exe, never in the project root or AppData.
+    /// Only `--portable` affects the config search; the field takes effect once loaded.
+    pub fn is_portable() -> bool {
+        PORTABLE.load(Ordering::Relaxed) || std::env::args().any(|a| a == "--portable")
+    }
+
+    /// WebView2 profile in portable mode: `<exe_name>.webview2` beside the exe
+    pub fn portable_data_dir() -> Option<PathBuf> {
+        let exe = std::env::current_exe().ok()?;
+        let name = format!("{}.webview2", Self::exe_stem()?);
+        exe.parent().map(|dir| dir.join(name))
+    }
 
-        // In debug mode, check project root first (via CARGO_MANIFEST_DIR)
+    /// Project root (parent of CARGO_MANIFEST_DIR) when running a debug build via cargo.
+    /// Always None in release builds and in portable mode.
+    fn project_root() -> Option<PathBuf> {
         #[cfg(debug_assertions)]
-        {
+        if !Self::is_portable() {
             if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
-                let project_root = PathBuf::from(manifest_dir)
-                    .parent()
-                    .map(|p| p.to_path_buf())
-                    .unwrap_or_default();
-                let path = project_root.join(&config_name);
-                if path.exists() {
-                    return Ok(path);
-                }
+                return PathBuf::from(manifest_dir).parent().map(|p| p.to_path_buf());
             }
         }
+        None
+    }
 
-        // Check beside the executable
-        let exe_dir = std::env::current_exe()?
-            .parent()
-            .ok_or("Cannot determine exe directory")?
-            .to_path_buf();
-        let path = exe_dir.join(&config_name);
-        if path.exists() {
-            return Ok(path);
+    /// Path for the window state file: `<exe_name>.window.json` beside the config, or
+    /// `<exe_name>.window.<slot>.json` for instances after the first
+    pub fn window_state_path() -> Option<PathBuf> {
+        match INSTANCE_SLOT.load(Ordering::Relaxed) {
+            1 => Self::sidecar_path("window.json"),
+            slot => Self::sidecar_path(&format!("window.{}.json", slot)),
         }
+    }
 
-        Err(format!("{} not found", config_name).into())
+    /// With several instances, each holds a slot (1 = the first) and keeps its own
+    /// window state
+    pub fn set_instance_slot(slot: u32) {
+        INSTANCE_SLOT.store(slot.max(1), Ordering::Relaxed);
     }
 
-    /// Path for the window state file: `<exe_name>.window.json` beside the config
-    pub fn window_state_path() -> Option<PathBuf> {
-        let exe_name = std::env::current_exe()
-            .ok()
-            .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))?;
-        let filename = format!("{}.window.json", exe_name);
+    /// Path for the last-visited URL (`resume_last_url`): `<exe_name>.last_url` beside the config
+    pub fn last_url_path() -> Option<PathBuf> {
+        Self::sidecar_path("last_url")
+    }
 
-        // In debug mode, check project root first
-        #[cfg(debug_assertions)]
-        {
-            if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
-                if let Some(project_root) = PathBuf::from(manifest_dir).parent() {
-                    return Some(project_root.join(&filename));
-                }
-            }
+    /// Last-visited http(s) URL, if one was stored
+    pub fn load_last_url() -> Option<String> {
+        let contents = std::fs::read_to_string(Self::last_url_path()?).ok()?;
+        let url = contents.trim();
+        let lower = url.to_ascii_lowercase();
+        if lower.starts_with("http://") || lower.starts_with("https://") {
+            Some(url.to_string())
+        } else {
+            None
+        }
+    }
+
+    pub fn save_last_url(url: &str) {
+        if let Some(path) = Self::last_url_path() {
+            let _ = std::fs::write(path, url);
         }
+    }
 
-        // Beside the executable
-        std::env::current_exe()
-            .ok()
-            .and_then(|p| p.parent().map(|d| d.join(&filename)))
+    /// Forget the last-visited URL so the next launch starts at `url` again
+    pub fn clear_last_url() {
+        if let Some(path) = Self::last_url_path() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    /// Path for the version the cache was last used with (`clear_cache_on_update`):
+    /// `<exe_name>.version` beside the config
+    pub fn version_marker_path() -> Option<PathBuf> {
+        Self::sidecar_path("version")
+    }
+
+    pub fn load_version_marker() -> Option<String> {
+        let contents = std::fs::read_to_string(Self::version_marker_path()?).ok()?;
+        Some(contents.trim().to_string())
+    }
+
+    pub fn save_version_marker(version: &str) {
+        if let Some(path) = Self::version_marker_path() {
+            let _ = std::fs::write(path, version);
+        }
+    }
+
+    /// Path for the recently visited pages (`recent_pages`): `<exe_name>.history` beside
+    /// the config, one URL per line, most recent first
+    pub fn history_path() -> Option<PathBuf> {
+        Self::sidecar_path("history")
+    }
+
+    /// Recently visited http(s) pages, most recent first
+    pub fn load_history() -> Vec<String> {
+        let Some(contents) = Self::history_path().and_then(|p| std::fs::read_to_string(p).ok())
+        else {
+            return Vec::new();
+        };
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|url| {
+                let lower = url.to_ascii_lowercase();
+                lower.starts_with("http://") || lower.starts_with("https://")
+            })
+            .take(MAX_HISTORY)
+            .map(String::from)
+            .collect()
+    }
+
+    /// Move `url` to the top of the recent pages, keeping the list at `MAX_HISTORY`
+    pub fn record_history(url: &str) {
+        let Some(path) = Self::history_path() else { return };
+        let mut history = Self::load_history();
+        if history.first().map(String::as_str) == Some(url) {
+            return;
+        }
+        history.retain(|u| u != url);
+        history.insert(0, url.to_string());
+        history.truncate(MAX_HISTORY);
+        let _ = std::fs::write(path, history.join("\n"));
+    }
+
+    /// Path for the per-site zoom levels (`zoom_per_site`): `<exe_name>.zoom.json` beside
+    /// the config, origin → zoom factor
+    pub fn site_zoom_path() -> Option<PathBuf> {
+        Self::sidecar_path("zoom.json")
+    }
+
+    /// Saved zoom factor per origin, empty when none are saved
+    pub fn load_site_zoom() -> HashMap<String, f64> {
+        Self::site_zoom_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Replace the saved per-site zoom levels
+    pub fn save_site_zoom(zoom: &HashMap<String, f64>) {
+        let Some(path) = Self::site_zoom_path() else { return };
+        if let Ok(json) = serde_json::to_string_pretty(zoom) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    /// Path for the log file: `<exe_name>.log` beside the config
+    pub fn log_path() -> Option<PathBuf> {
+        Self::sidecar_path("log")
+    }
+
+    /// Path for a `<exe_name>.<extension>` file that lives beside the config. Portable
+    /// mode keeps them beside the exe even with `--config`.
+    fn sidecar_path(extension: &str) -> Option<PathBuf> {
+        let exe_name = Self::exe_stem()?;
+        let filename = format!("{}.{}", exe_name, extension);
+        let override_path = config_override_arg().filter(|_| !Self::is_portable());
+        let cwd = std::env::current_dir().ok()?;
+
+        let dir = sidecar_dir(
+            override_path.as_deref(),
+            &cwd,
+            Self::project_root().as_deref(),
+            &Self::exe_dir()?,
+        );
+        Some(dir.join(filename))
     }
 
     /// Parse the single-instance mode from config.
@@ -109,20 +846,519 @@ impl AppConfig {
         }
     }
 
+    /// How long "last" mode waits for killed instances to exit (default 2 s)
+    pub fn kill_wait(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.kill_wait_ms.unwrap_or(2000))
+    }
+
+    /// Windows AppUserModelID used for taskbar grouping and pinning. Uses the configured
+    /// value, or derives `TauriWebApp.<exe_name>.<hash of exe path>` so same-named
+    /// wrappers living in different folders don't share a taskbar group.
+    /// Limited to 128 characters with no spaces, as Windows requires.
+    pub fn app_user_model_id(&self) -> String {
+        let id = if !self.app_user_model_id.trim().is_empty() {
+            self.app_user_model_id.trim().to_string()
+        } else {
+            let exe_path = std::env::current_exe()
+                .map(|p| p.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            format!(
+                "TauriWebApp.{}.{:08x}",
+                Self::exe_stem().unwrap_or_else(|| "app".to_string()),
+                fnv1a(exe_path.as_bytes()) as u32
+            )
+        };
+
+        id.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+            .take(128)
+            .collect()
+    }
+
+    /// Whether a session closed while maximized reopens un-maximized at its
+    /// saved normal size (instead of maximized)
+    pub fn restore_maximized_as_normal(&self) -> bool {
+        self.restore_maximized_as_normal.eq_ignore_ascii_case("on")
+    }
+
+    /// How long the window must stay maximized before the maximized flag is saved
+    /// (default 500 ms), so a passing maximize during Snap Assist isn't remembered
+    pub fn maximize_save_delay(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.maximize_save_delay_ms.unwrap_or(500))
+    }
+
+    /// Smallest saved width and height that is restored (default 200 px). Anything
+    /// smaller is taken for a broken state file, but never less than 1 px.
+    pub fn min_restore_size(&self) -> u32 {
+        self.min_restore_size.unwrap_or(200).max(1)
+    }
+
+    /// Reopen fullscreen, on the same monitor, when the window was closed fullscreen
+    pub fn remember_fullscreen(&self) -> bool {
+        self.remember_fullscreen.eq_ignore_ascii_case("on")
+    }
+
+    /// Custom URL scheme handled by this exe (`myapp` for `myapp://` links), if configured
+    pub fn protocol_scheme(&self) -> Option<&str> {
+        let scheme = self.protocol_scheme.trim().trim_end_matches("://").trim_end_matches(':');
+        (!scheme.is_empty()).then_some(scheme)
+    }
+
+    /// Native title bar and borders (default on); off = frameless window
+    pub fn decorations(&self) -> bool {
+        !self.decorations.eq_ignore_ascii_case("off")
+    }
+
+    /// Floating widget preset (`hud`): small, centered, frameless, on top
+    pub fn hud(&self) -> bool {
+        self.hud.eq_ignore_ascii_case("on")
+    }
+
+    /// Fill in the options a preset implies. Options the config sets itself are kept,
+    /// so any part of a preset can be overridden.
+    pub fn apply_presets(&mut self) {
+        let default = |field: &mut String, value: &str| {
+            if field.trim().is_empty() {
+                *field = value.to_string();
+            }
+        };
+        if self.hud() {
+            default(&mut self.decorations, "off");
+            default(&mut self.always_on_top, "on");
+            default(&mut self.skip_taskbar, "on");
+            default(&mut self.transparent, "on");
+            default(&mut self.resizable, "off");
+        }
+        if self.unattended.eq_ignore_ascii_case("on") {
+            default(&mut self.script_dialogs, "off");
+            default(&mut self.permission_prompts, "off");
+            default(&mut self.downloads, "off");
+            default(&mut self.autofill, "off");
+        }
+    }
+
+    /// Drop shadow around a frameless window (default on)
+    pub fn window_shadow(&self) -> bool {
+        !self.window_shadow.eq_ignore_ascii_case("off")
+    }
+
+    /// Aero Snap, edge resizing and page-declared drag regions for a frameless window.
+    /// Only applies with `decorations: off`.
+    pub fn frameless_snap(&self) -> bool {
+        !self.decorations() && self.frameless_snap.eq_ignore_ascii_case("on")
+    }
+
+    /// Whether the system menu items and the window subclass behind them are set up
+    /// (default on). Locked-down environments may flag window subclassing.
+    pub fn system_menu(&self) -> bool {
+        !self.system_menu.eq_ignore_ascii_case("off")
+    }
+
+    /// Configured features that work (fully or in part) through the window subclass, so
+    /// are unavailable or limited with `system_menu: off`
+    pub fn subclass_features(&self) -> Vec<&'static str> {
+        let on = |value: &str| value.eq_ignore_ascii_case("on");
+        [
+            ("frameless_snap", self.frameless_snap()),
+            ("app_region", on(&self.app_region)),
+            ("content_aspect", self.content_aspect().is_some()),
+            ("autohide_titlebar", self.autohide_titlebar()),
+            ("recent_pages", on(&self.recent_pages)),
+            ("reload_on_resume", on(&self.reload_on_resume)),
+            ("reload_on_time_change", on(&self.reload_on_time_change)),
+            ("resume_last_url", on(&self.resume_last_url)),
+            ("single_window_per_url", on(&self.single_window_per_url)),
+            ("protocol_scheme", self.protocol_scheme().is_some()),
+            ("defer_navigation", on(&self.defer_navigation)),
+            ("jump_list", on(&self.jump_list)),
+            ("forwarding --url to the running instance", self.instance_mode() == Some("first")),
+            ("accessibility (following setting changes)", on(&self.accessibility)),
+            // Not a setting: WM_ENDSESSION is the only save before a sign-out ends us
+            ("saving the window state at sign-out", true),
+            // Not a setting either: the window follows moves between monitors of different DPI
+            ("resizing and re-rendering on a DPI change", true),
+        ]
+        .into_iter()
+        .filter(|&(_, enabled)| enabled)
+        .map(|(name, _)| name)
+        .collect()
+    }
+
+    /// Height in CSS px of the strip at the top of a frameless window that drags it like
+    /// a title bar. None when unset, 0, or the window has decorations.
+    pub fn caption_height(&self) -> Option<u32> {
+        self.caption_height.filter(|&height| height > 0 && !self.decorations())
+    }
+
+    /// Whether double-clicking a frameless window's drag area toggles maximize (default on)
+    pub fn titlebar_doubleclick(&self) -> bool {
+        !self.titlebar_doubleclick.eq_ignore_ascii_case("off")
+    }
+
+    /// Hide the title bar while maximized (only for decorated windows)
+    pub fn autohide_titlebar(&self) -> bool {
+        self.decorations() && self.autohide_titlebar.eq_ignore_ascii_case("on")
+    }
+
+    /// Per-instance cascade step in pixels (X, Y). Defaults to (32, 32);
+    /// negative values cascade up/left.
+    pub fn cascade_step(&self) -> (i32, i32) {
+        (
+            self.cascade_step_x.unwrap_or(32),
+            self.cascade_step_y.unwrap_or(32),
+        )
+    }
+
+    /// `content_aspect` as width / height, from `"16:9"` or `"1.7778"`. None when unset
+    /// or malformed.
+    pub fn content_aspect(&self) -> Option<f64> {
+        let value = self.content_aspect.trim();
+        let ratio = match value.split_once(':') {
+            Some((w, h)) => w.trim().parse::<f64>().ok()? / h.trim().parse::<f64>().ok()?,
+            None => value.parse::<f64>().ok()?,
+        };
+        (ratio.is_finite() && ratio > 0.0).then_some(ratio)
+    }
+
+    /// `splash_bg` as RGB, from `#rgb` or `#rrggbb`. None when unset or malformed.
+    pub fn splash_bg(&self) -> Option<(u8, u8, u8)> {
+        let hex = self.splash_bg.trim().strip_prefix('#')?;
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+        match hex.len() {
+            3 => {
+                let (r, g, b) = (&hex[0..1], &hex[1..2], &hex[2..3]);
+                Some((channel(r)? * 17, channel(g)? * 17, channel(b)? * 17))
+            }
+            6 => Some((channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?)),
+            _ => None,
+        }
+    }
+
+    /// Zoom factor pages open at (`default_zoom`), 1 when unset, kept within 0.25–5
+    pub fn default_zoom(&self) -> f64 {
+        self.default_zoom.unwrap_or(1.0).clamp(0.25, 5.0)
+    }
+
+    /// Text size multiplier, or None when unset, 1 or outside 0.5–3
+    pub fn font_scale(&self) -> Option<f64> {
+        self.font_scale.filter(|&scale| (0.5..=3.0).contains(&scale) && scale != 1.0)
+    }
+
+    /// Navigation timeout, or None when 0 (wait forever). When unset, 30 s if a
+    /// `loading_timeout_action` is configured, else None.
+    pub fn navigation_timeout(&self) -> Option<std::time::Duration> {
+        let default = (!self.loading_timeout_action.trim().is_empty()).then_some(30);
+        self.navigation_timeout_secs
+            .or(default)
+            .filter(|&secs| secs > 0)
+            .map(std::time::Duration::from_secs)
+    }
+
+    /// `loading_timeout_action`: `"retry"`, `"fallback"` (needs a usable `fallback_url`)
+    /// or the default `"show-error"`
+    pub fn loading_timeout_action(&self) -> LoadingTimeoutAction {
+        match self.loading_timeout_action.trim().to_ascii_lowercase().as_str() {
+            "retry" => LoadingTimeoutAction::Retry,
+            "fallback" => match self.fallback_url() {
+                Some(url) => LoadingTimeoutAction::Fallback(url),
+                None => LoadingTimeoutAction::ShowError,
+            },
+            _ => LoadingTimeoutAction::ShowError,
+        }
+    }
+
+    /// How long the app must have run before a quit is accepted, or None when unset or 0
+    pub fn min_uptime(&self) -> Option<std::time::Duration> {
+        self.min_uptime_secs
+            .filter(|&secs| secs > 0)
+            .map(std::time::Duration::from_secs)
+    }
+
+    /// How long the page may go without answering the freeze watchdog, or None when
+    /// unset or 0 (off)
+    pub fn freeze_timeout(&self) -> Option<std::time::Duration> {
+        self.freeze_timeout_secs
+            .filter(|&secs| secs > 0)
+            .map(std::time::Duration::from_secs)
+    }
+
+    /// Memory limit for the webview's processes in MB, or None when unset or 0 (off)
+    pub fn max_memory_mb(&self) -> Option<u64> {
+        self.max_memory_mb.filter(|&mb| mb > 0)
+    }
+
+    /// URL schemes handed to the system shell instead of the webview, lowercased and
+    /// without the trailing ':'. Defaults to mailto and tel; `[]` disables the handoff.
+    pub fn external_schemes(&self) -> Vec<String> {
+        match &self.external_schemes {
+            Some(schemes) => schemes
+                .iter()
+                .map(|s| s.trim().trim_end_matches(':').to_ascii_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            None => vec!["mailto".to_string(), "tel".to_string()],
+        }
+    }
+
+    /// Minimum TLS minor version to require beyond WebView2's own floor of TLS 1.2,
+    /// i.e. Some(3) for `"min_tls": "1.3"`. None when unset, 1.2 and below, or invalid
+    /// (logged).
+    pub fn min_tls(&self) -> Option<u8> {
+        let value = self.min_tls.trim().to_ascii_lowercase();
+        let version = value.strip_prefix("tls").unwrap_or(&value).trim();
+        match version {
+            "1.3" => Some(3),
+            "" | "1.0" | "1.1" | "1.2" => None,
+            _ => {
+                logging::log(&format!("Ignoring invalid min_tls: {:?}", self.min_tls));
+                None
+            }
+        }
+    }
+
+    /// Layout named by `--layout <name>`. None when no layout was requested;
+    /// Some(Err) when the name isn't in `layouts`.
+    pub fn launch_layout(&self) -> Option<Result<&Layout, String>> {
+        let name = arg_value("--layout")?;
+        Some(self.layouts.get(&name).ok_or(name))
+    }
+
+    /// Parsed `exit_gesture`. None when unset or malformed (logged).
+    pub fn exit_gesture(&self) -> Option<ExitGesture> {
+        if self.exit_gesture.trim().is_empty() {
+            return None;
+        }
+        let gesture = ExitGesture::parse(&self.exit_gesture);
+        if gesture.is_none() {
+            logging::log(&format!("Ignoring invalid exit_gesture: {:?}", self.exit_gesture));
+        }
+        gesture
+    }
+
+    /// Parsed `clear_schedule`. None when unset or malformed (logged).
+    pub fn clear_schedule(&self) -> Option<ClearSchedule> {
+        if self.clear_schedule.trim().is_empty() {
+            return None;
+        }
+        let schedule = ClearSchedule::parse(&self.clear_schedule);
+        if schedule.is_none() {
+            logging::log(&format!("Ignoring invalid clear_schedule: {:?}", self.clear_schedule));
+        }
+        schedule
+    }
+
+    /// Validated `schedule` entries, in config order (the first active one wins)
+    pub fn schedule(&self) -> Result<Vec<ScheduleSlot>, String> {
+        self.schedule.iter().map(ScheduleSlot::parse).collect()
+    }
+
+    /// Parsed `shortcuts`. Err names the first entry with an unknown key or action.
+    pub fn shortcuts(&self) -> Result<Vec<Shortcut>, String> {
+        self.shortcuts.iter().map(|(spec, action)| Shortcut::parse(spec, action)).collect()
+    }
+
+    /// Browsing data kinds the clear schedule removes, lowercased.
+    /// Defaults to cookies, cache and storage.
+    pub fn clear_data(&self) -> Vec<String> {
+        match &self.clear_data {
+            Some(kinds) => kinds.iter().map(|k| k.trim().to_ascii_lowercase()).collect(),
+            None => ["cookies", "cache", "storage"].map(String::from).to_vec(),
+        }
+    }
+
+    /// Parsed `geometry` spec. None when unset; Some(Err) when set but malformed.
+    pub fn geometry(&self) -> Option<Result<Geometry, String>> {
+        if self.geometry.trim().is_empty() {
+            return None;
+        }
+        Some(Geometry::parse(&self.geometry).ok_or_else(|| self.geometry.clone()))
+    }
+
+    /// Configured `locale` (a BCP 47 tag like `de-DE`), or None when unset or not a
+    /// plausible tag. It ends up on the WebView2 command line, hence the strict check.
+    pub fn locale(&self) -> Option<&str> {
+        let locale = self.locale.trim();
+        let valid = !locale.is_empty()
+            && locale.len() <= 35
+            && locale.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        valid.then_some(locale)
+    }
+
+    /// Hosts allowed to sign in with the Windows account (`integrated_auth`): the host
+    /// of `url` and its subdomains, in Chromium's `--auth-server-allowlist` format.
+    /// None when off.
+    pub fn integrated_auth_allowlist(&self) -> Option<String> {
+        if !self.integrated_auth.eq_ignore_ascii_case("on") {
+            return None;
+        }
+        let url: tauri::Url = self.url.parse().ok()?;
+        let host = url.host_str()?;
+        Some(format!("{},*.{}", host, host))
+    }
+
+    /// Chromium `WebContentsForceDark` feature string for the `force_dark_mode` value,
+    /// or None when force-dark is off (or the value is unknown):
+    /// - `"on"`: Chromium's default force-dark algorithm
+    /// - `"selective"`: CIELAB-based inversion that only darkens elements that are light
+    ///   (thresholds as in chrome://flags), images inverted selectively
+    /// - `"aggressive"`: simple RGB inversion of all non-image content
+    pub fn force_dark_feature(&self) -> Option<&'static str> {
+        match self.force_dark_mode.to_lowercase().as_str() {
+            "on" => Some("WebContentsForceDark"),
+            "selective" => Some(
+                "WebContentsForceDark:inversion_method/cielab_based/image_behavior/selective\
+                 /foreground_lightness_threshold/150/background_lightness_threshold/205",
+            ),
+            "aggressive" => {
+                Some("WebContentsForceDark:inversion_method/rgb_based/image_behavior/none")
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether diagnostics commands and the Ctrl+Alt+D overlay are available to the page
+    pub fn diagnostics_enabled(&self) -> bool {
+        self.diagnostics.eq_ignore_ascii_case("on")
+    }
+
+    /// What a page calling `window.close()` does: "close" (default), "hide" or "ignore"
+    pub fn window_close_request(&self) -> &str {
+        match self.window_close_request.to_lowercase().as_str() {
+            "hide" => "hide",
+            "ignore" => "ignore",
+            _ => "close",
+        }
+    }
+
+    /// Whether a window hidden to the tray keeps its taskbar button: "remove" (default)
+    /// or "keep", which minimizes instead of hiding so the thumbnail stays
+    pub fn tray_keeps_taskbar(&self) -> bool {
+        self.tray_taskbar.eq_ignore_ascii_case("keep")
+    }
+
+    /// What happens to files dropped on the window: "page" (default, the page's own
+    /// drop handlers get them), "ignore" or "open"
+    pub fn file_drop(&self) -> &str {
+        match self.file_drop.to_lowercase().as_str() {
+            "ignore" => "ignore",
+            "open" => "open",
+            _ => "page",
+        }
+    }
+
+    /// Whether DevTools can be opened: `devtools` when set, else only in debug builds
+    pub fn devtools(&self) -> bool {
+        match self.devtools.to_lowercase().as_str() {
+            "on" => true,
+            "off" => false,
+            _ => cfg!(debug_assertions),
+        }
+    }
+
+    /// Whether the navigate/reload/go_back/go_forward commands are available to the page
+    pub fn navigation_commands_enabled(&self) -> bool {
+        self.navigation_commands.eq_ignore_ascii_case("on")
+    }
+
+    /// URL pattern matching every page on the configured URL's origin
+    /// (e.g. `https://example.com/*`), for granting the page IPC access
+    pub fn origin_pattern(&self) -> Option<String> {
+        let url: tauri::Url = self.url.parse().ok()?;
+        let host = url.host_str()?;
+        Some(match url.port() {
+            Some(port) => format!("{}://{}:{}/*", url.scheme(), host, port),
+            None => format!("{}://{}/*", url.scheme(), host),
+        })
+    }
+
+    /// Whether `url` is on the configured URL's origin, the pages `origin_pattern` grants
+    /// IPC access to
+    pub fn is_own_origin(&self, url: &str) -> bool {
+        match (self.url.parse::<tauri::Url>(), url.parse::<tauri::Url>()) {
+            (Ok(own), Ok(url)) => own.origin() == url.origin(),
+            _ => false,
+        }
+    }
+
+    /// Whether each executable gets its own WebView2 user data folder
+    /// (`"per-exe"`) instead of the shared default one
+    pub fn per_exe_user_data(&self) -> bool {
+        self.user_data_folder.eq_ignore_ascii_case("per-exe")
+    }
+
     pub fn resolve_icon_path(&self) -> Option<PathBuf> {
-        if self.icon.is_empty() {
+        Self::resolve_file(&self.icon)
+    }
+
+    /// Path of the `alert_icon` shown while the page asks for attention
+    pub fn resolve_alert_icon_path(&self) -> Option<PathBuf> {
+        Self::resolve_file(&self.alert_icon)
+    }
+
+    /// Version the cached content belongs to: `content_version` when set (for web app
+    /// releases), else the wrapper's own version
+    pub fn content_version(&self) -> String {
+        match self.content_version.trim() {
+            "" => crate::APP_VERSION.to_string(),
+            version => version.to_string(),
+        }
+    }
+
+    /// Failover targets in order of preference: `url`, then the http(s) entries of `urls`
+    /// that aren't `url` itself. Only one entry means there is nothing to fail over to.
+    pub fn failover_urls(&self) -> Vec<tauri::Url> {
+        let mut targets: Vec<tauri::Url> = Vec::new();
+        for value in std::iter::once(&self.url).chain(&self.urls) {
+            let Ok(url) = value.trim().parse::<tauri::Url>() else { continue };
+            if matches!(url.scheme(), "http" | "https") && !targets.contains(&url) {
+                targets.push(url);
+            }
+        }
+        targets
+    }
+
+    /// `fallback_url` as a URL: http(s) and file URLs as given, anything else as a path
+    /// to a local file (absolute or relative to the exe). None when unset or not found.
+    pub fn fallback_url(&self) -> Option<tauri::Url> {
+        let value = self.fallback_url.trim();
+        if let Ok(url) = value.parse::<tauri::Url>() {
+            if matches!(url.scheme(), "http" | "https" | "file") {
+                return Some(url);
+            }
+        }
+        let path = Self::resolve_file(value)?;
+        tauri::Url::from_file_path(std::fs::canonicalize(&path).unwrap_or(path)).ok()
+    }
+
+    /// Folder of the bundled web app (`bundle_dir`), if configured and present
+    pub fn resolve_bundle_dir(&self) -> Option<PathBuf> {
+        Self::resolve_file(&self.bundle_dir).filter(|dir| dir.is_dir())
+    }
+
+    /// Path of the Netscape-format `cookies_file`, if configured and present
+    pub fn resolve_cookies_file(&self) -> Option<PathBuf> {
+        Self::resolve_file(&self.cookies_file)
+    }
+
+    /// Resolve a configured file path: absolute, or relative to the exe directory
+    /// (or the project root in debug builds). None when empty or not found.
+    fn resolve_file(path: &str) -> Option<PathBuf> {
+        if path.is_empty() {
             return None;
         }
 
-        let icon_path = PathBuf::from(&self.icon);
-        if icon_path.is_absolute() && icon_path.exists() {
-            return Some(icon_path);
+        let file_path = PathBuf::from(path);
+        if file_path.is_absolute() && file_path.exists() {
+            return Some(file_path);
         }
 
         // Resolve relative to exe directory
         if let Ok(exe) = std::env::current_exe() {
             if let Some(exe_dir) = exe.parent() {
-                let resolved = exe_dir.join(&icon_path);
+                let resolved = exe_dir.join(&file_path);
                 if resolved.exists() {
                     return Some(resolved);
                 }
@@ -130,15 +1366,10 @@ impl AppConfig {
         }
 
         // In debug mode, also resolve relative to project root
-        #[cfg(debug_assertions)]
-        {
-            if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
-                if let Some(project_root) = PathBuf::from(manifest_dir).parent() {
-                    let resolved = project_root.join(&icon_path);
-                    if resolved.exists() {
-                        return Some(resolved);
-                    }
-                }
+        if let Some(project_root) = Self::project_root() {
+            let resolved = project_root.join(&file_path);
+            if resolved.exists() {
+                return Some(resolved);
             }
         }
 
@@ -148,16 +1379,190 @@ impl AppConfig {
 
 impl WindowState {
     pub fn load() -> Option<Self> {
-        let path = AppConfig::window_state_path()?;
-        let contents = std::fs::read_to_string(&path).ok()?;
+        Self::load_from(&AppConfig::window_state_path()?)
+    }
+
+    /// State of the first instance, which a further instance without a state of its own
+    /// starts from
+    pub fn load_first() -> Option<Self> {
+        Self::load_from(&AppConfig::sidecar_path("window.json")?)
+    }
+
+    fn load_from(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
         serde_json::from_str(&contents).ok()
     }
 
+    /// Save the state, logging a failure. Saving runs on every move/resize, so a failure
+    /// (typically a read-only file or folder on locked-down machines) is only reported
+    /// once per session.
     pub fn save(&self) {
-        if let Some(path) = AppConfig::window_state_path() {
-            if let Ok(json) = serde_json::to_string_pretty(self) {
-                let _ = std::fs::write(path, json);
+        if let Err(e) = self.try_save() {
+            static REPORTED: AtomicBool = AtomicBool::new(false);
+            if !REPORTED.swap(true, Ordering::Relaxed) {
+                logging::log(&format!(
+                    "Cannot save window state: {} (window geometry will not persist)",
+                    e
+                ));
             }
         }
     }
+
+    /// Written to a temporary file and renamed over the state file, so instances closing
+    /// at the same moment (taskbar "Close all windows") can't leave a half-written file.
+    pub fn try_save(&self) -> std::io::Result<()> {
+        let path = AppConfig::window_state_path().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no location for the state file")
+        })?;
+        let json = serde_json::to_string_pretty(self)?;
+
+        let temp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        let result = std::fs::write(&temp, json).and_then(|_| std::fs::rename(&temp, &path));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp);
+        }
+        result.map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Config file search, in precedence order:
+/// 1. `override_path` (from `--config <path>`, relative to `cwd`) — used even if
+///    missing, so the error names the file the user asked for
+/// 2. `project_root` (debug builds run via cargo)
+/// 3. Beside the executable
+///
+/// Takes everything it depends on as parameters (including the existence check)
+/// rather than reading the environment, so the precedence is easy to reason about.
+fn search_config_path(
+    config_name: &str,
+    override_path: Option<&Path>,
+    cwd: &Path,
+    project_root: Option<&Path>,
+    exe_dir: &Path,
+    exists: impl Fn(&Path) -> bool,
+) -> Option<PathBuf> {
+    if let Some(path) = override_path {
+        return Some(cwd.join(path));
+    }
+
+    project_root
+        .into_iter()
+        .chain(std::iter::once(exe_dir))
+        .map(|dir| dir.join(config_name))
+        .find(|path| exists(path))
+}
+
+/// Directory for the `<exe_name>.*` sidecar files: the folder of the `--config` file,
+/// else the project root (debug builds) or the executable's folder, matching where
+/// `search_config_path` finds the config
+fn sidecar_dir(
+    override_path: Option<&Path>,
+    cwd: &Path,
+    project_root: Option<&Path>,
+    exe_dir: &Path,
+) -> PathBuf {
+    override_path
+        .and_then(|path| cwd.join(path).parent().map(Path::to_path_buf))
+        .or_else(|| project_root.map(Path::to_path_buf))
+        .unwrap_or_else(|| exe_dir.to_path_buf())
+}
+
+/// Value of the `--config <path>` command-line argument, if given (`--config -`, the
+/// config on stdin, names no file)
+fn config_override_arg() -> Option<PathBuf> {
+    arg_value("--config").filter(|path| path != "-").map(PathBuf::from)
+}
+
+/// Value following `flag` on the command line (`--flag <value>`), if given
+pub(crate) fn arg_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+    }
+    None
+}
+
+/// FNV-1a hash — stable across Rust versions (unlike `DefaultHasher`), so derived
+/// identifiers stay the same between builds
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAME: &str = "app.json";
+
+    fn search(
+        override_path: Option<&str>,
+        project_root: Option<&str>,
+        existing: &[&str],
+    ) -> Option<PathBuf> {
+        search_config_path(
+            NAME,
+            override_path.map(Path::new),
+            Path::new("/work"),
+            project_root.map(Path::new),
+            Path::new("/opt/app"),
+            |path| existing.iter().any(|e| Path::new(e) == path),
+        )
+    }
+
+    #[test]
+    fn override_wins_even_when_missing() {
+        let found = search(Some("/etc/music.json"), Some("/src"), &["/opt/app/app.json"]);
+        assert_eq!(found, Some(PathBuf::from("/etc/music.json")));
+    }
+
+    #[test]
+    fn relative_override_resolves_against_cwd() {
+        let found = search(Some("configs/music.json"), None, &[]);
+        assert_eq!(found, Some(PathBuf::from("/work/configs/music.json")));
+    }
+
+    #[test]
+    fn project_root_before_exe_dir() {
+        let existing = ["/src/app.json", "/opt/app/app.json"];
+        assert_eq!(search(None, Some("/src"), &existing), Some(PathBuf::from("/src/app.json")));
+    }
+
+    #[test]
+    fn beside_exe_when_project_root_has_none() {
+        let found = search(None, Some("/src"), &["/opt/app/app.json"]);
+        assert_eq!(found, Some(PathBuf::from("/opt/app/app.json")));
+    }
+
+    #[test]
+    fn not_found_anywhere() {
+        assert_eq!(search(None, Some("/src"), &[]), None);
+        assert_eq!(search(None, None, &["/work/app.json"]), None);
+    }
+
+    #[test]
+    fn minute_of_day() {
+        assert_eq!(parse_minute_of_day("08:30"), Some(510));
+        assert_eq!(parse_minute_of_day("24:00"), Some(1440));
+        assert_eq!(parse_minute_of_day("24:01"), None);
+        assert_eq!(parse_minute_of_day("12:60"), None);
+        assert_eq!(parse_minute_of_day("1100:00"), None);
+        assert_eq!(parse_minute_of_day("65535:00"), None);
+    }
+
+    #[test]
+    fn sidecars_follow_the_config() {
+        let cwd = Path::new("/work");
+        let exe_dir = Path::new("/opt/app");
+        let dir = |override_path: Option<&str>, root: Option<&str>| {
+            sidecar_dir(override_path.map(Path::new), cwd, root.map(Path::new), exe_dir)
+        };
+        assert_eq!(dir(Some("/etc/music.json"), Some("/src")), PathBuf::from("/etc"));
+        assert_eq!(dir(Some("configs/music.json"), None), PathBuf::from("/work/configs"));
+        assert_eq!(dir(None, Some("/src")), PathBuf::from("/src"));
+        assert_eq!(dir(None, None), PathBuf::from("/opt/app"));
+    }
 }
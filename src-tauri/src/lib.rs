@@ -1,54 +1,357 @@
+#[cfg(target_os = "windows")]
+mod bridge;
+mod bundle;
+mod commands;
 mod config;
+mod logging;
+mod protocol;
+#[cfg(target_os = "windows")]
+mod webview;
 
-use config::{AppConfig, WindowState};
+use config::{AppConfig, ClearSchedule, Geometry, Layout, ScheduleSlot, WindowState};
+use std::path::PathBuf;
 use tauri::Manager;
 
 const APP_VERSION: &str = "3.0.7";
 const APP_REPO_URL: &str = "https://github.com/AtmanActive/Tauri2_Any_WebApp_Wrapper";
 
+/// The main window, for handlers that have no other way to reach it
+/// (e.g. the window subclass proc)
+static MAIN_WINDOW: std::sync::OnceLock<tauri::WebviewWindow> = std::sync::OnceLock::new();
+
+/// Configured `window_class`, used to find other instances' windows. Set before
+/// single-instance enforcement; unset means the default class (match any titled window).
+static WINDOW_CLASS: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+/// Set when `frameless_snap` is active, so the subclass proc reports resize borders
+static FRAMELESS_SNAP: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Set when `app_region` is on, so the subclass proc makes CSS drag regions drag the window
+static APP_REGION: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// `content_aspect` (width / height), so the subclass proc letterboxes the webview on resize
+static CONTENT_ASPECT: std::sync::OnceLock<f64> = std::sync::OnceLock::new();
+
+/// Latest page title, for `title_template`'s `{title}`
+static PAGE_TITLE: std::sync::Mutex<String> = std::sync::Mutex::new(String::new());
+
+/// Internet connectivity, for `title_template`'s `{status}`
+static ONLINE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(true);
+
+/// Set with `--selftest`: exit with the result of the first page load (CI smoke tests)
+static SELFTEST: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Process ID of the `--supervised` parent, which shares our exe name but is not an instance
+static SUPERVISOR_PID: std::sync::OnceLock<u32> = std::sync::OnceLock::new();
+
+/// Exit code a supervised child uses to ask its supervisor for an immediate relaunch
+const SUPERVISED_RESTART_CODE: i32 = 75;
+
+/// Exit code of an instance terminated by a newer one in single-instance `last` mode,
+/// which tells its supervisor to stop rather than relaunch it
+const REPLACED_EXIT_CODE: i32 = 76;
+
+/// Instances beyond this many share the first instance's window state
+#[cfg(target_os = "windows")]
+const MAX_INSTANCE_SLOTS: u32 = 64;
+
+/// How long a relaunched copy waits for the instance it replaces to exit
+const RELAUNCH_WAIT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// CPU slowdown factor applied to unfocused windows with `throttle_background`
+#[cfg(target_os = "windows")]
+const BACKGROUND_THROTTLE_RATE: f64 = 4.0;
+
+/// How long `--selftest` waits for the page before reporting a failure
+const SELFTEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Set when `accessibility` is on, so the subclass proc re-applies the OS settings
+/// to the page when they change
+static ACCESSIBILITY: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// When the process started, for `min_uptime_secs`
+static STARTED: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+
+/// Set while `defer_navigation` holds the window on the start page, until the first URL
+/// is pushed to it
+static NAVIGATION_DEFERRED: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+/// Set when `reload_on_resume` is on, so the subclass proc reloads the page after the
+/// machine wakes from sleep
+static RELOAD_ON_RESUME: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+/// Set when `reload_on_time_change` is on, so the subclass proc reloads the page after
+/// the system clock or time zone changes
+static RELOAD_ON_TIME_CHANGE: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+/// How long to wait after a resume from sleep before touching the window, so displays
+/// and the network have come back
+#[cfg(target_os = "windows")]
+const RESUME_SETTLE_TIME: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Set while `autohide_titlebar` has hidden the title bar of the maximized window
+static TITLEBAR_HIDDEN: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    STARTED.get_or_init(std::time::Instant::now);
+
+    // Jump list tasks relaunch the exe with a task flag. Handle those first and
+    // exit — before single-instance enforcement could kill the instance they target.
+    if let Some(task) = std::env::args().nth(1) {
+        if handle_jump_list_task(&task) {
+            std::process::exit(0);
+        }
+    }
+
     // Load config early — before Tauri creates the webview — so we can set
     // environment variables that affect WebView2 initialization.
     let config = match AppConfig::load() {
-        Ok(c) => c,
+        Ok(mut c) => {
+            // Presets (`hud`) fill in the options they imply
+            c.apply_presets();
+            c
+        }
         Err(e) => {
             show_config_error(&e.to_string());
             std::process::exit(1);
         }
     };
 
+    logging::init(config.logging.eq_ignore_ascii_case("on"));
+
+    // `--export-state <file>` / `--import-state <file>`: copy the saved window state
+    // out of or into this exe's `.window.json`, then exit
+    if let Some(code) = transfer_window_state() {
+        std::process::exit(code);
+    }
+
+    // `--supervised`: stay in the background as a small watchdog that relaunches the
+    // app whenever it exits abnormally. The window itself runs in the child process.
+    if let Some(pid) = config::arg_value("--supervised-child") {
+        if let Ok(pid) = pid.parse() {
+            let _ = SUPERVISOR_PID.set(pid);
+        }
+    } else if std::env::args().any(|a| a == "--supervised") {
+        std::process::exit(supervise());
+    }
+
+    // Dedicated displays: keep the UI responsive under load
+    set_process_priority(&config.process_priority);
+
+    // Taskbar identity — must be set before any window is created
+    set_app_user_model_id(&config.app_user_model_id());
+    let window_class = config.window_class.trim();
+    if !window_class.is_empty() {
+        let _ = WINDOW_CLASS.set(window_class.to_string());
+    }
+
+    // Set when this process was spawned to replace an instance that is shutting down
+    // (see `relaunch`). That instance may still be running for a moment, so it must
+    // not trigger single-instance enforcement or a cascade.
+    let relaunched = std::env::args().any(|a| a == "--relaunch");
+    // Its WebView2 profile stays locked until it has exited, so wait for it first
+    if let Some(pid) = config::arg_value("--relaunch").and_then(|pid| pid.parse().ok()) {
+        wait_for_exit(pid, RELAUNCH_WAIT);
+    }
+
+    // `--selftest`: load the page, print the result and exit. Never touches other
+    // instances, and fails if nothing loads in time.
+    let selftest = std::env::args().any(|a| a == "--selftest");
+    if selftest {
+        attach_parent_console();
+        SELFTEST.store(true, std::sync::atomic::Ordering::Relaxed);
+        std::thread::spawn(|| {
+            std::thread::sleep(SELFTEST_TIMEOUT);
+            let result = format!("SELFTEST FAIL (no page loaded in {:?})", SELFTEST_TIMEOUT);
+            logging::log(&result);
+            println!("{}", result);
+            std::process::exit(2);
+        });
+    }
+
+    // Custom URL protocol: explicit (un)registration flags, otherwise register on
+    // first run. A `<scheme>://` link goes to the running instance if there is one.
+    let mut protocol_link = None;
+    if let Some(scheme) = config.protocol_scheme() {
+        if std::env::args().any(|a| a == "--register-protocol") {
+            protocol::register(scheme, true);
+            std::process::exit(0);
+        }
+        if std::env::args().any(|a| a == "--unregister-protocol") {
+            protocol::unregister(scheme);
+            std::process::exit(0);
+        }
+        protocol::register(scheme, false);
+
+        protocol_link = std::env::args()
+            .skip(1)
+            .find_map(|arg| protocol::link_target(&config, scheme, &arg));
+        if let Some(url) = protocol_link.as_ref().filter(|_| !relaunched) {
+            if protocol::forward_to_running_instance(url) {
+                std::process::exit(0);
+            }
+        }
+    }
+
+    // `--url <url>` opens that page instead of the start page. In "first" mode it is
+    // handed to the running instance, which navigates there, before this one exits.
+    let url_arg = config::arg_value("--url")
+        .and_then(|arg| arg.parse::<tauri::Url>().ok())
+        .filter(|url| matches!(url.scheme(), "http" | "https"));
+    if let Some(url) = url_arg.as_ref().filter(|_| !relaunched) {
+        if config.instance_mode() == Some("first") && protocol::forward_to_running_instance(url)
+        {
+            std::process::exit(0);
+        }
+    }
+    // single_window_per_url: focus the instance already showing the --url page instead
+    // of opening a duplicate window
+    if let Some(url) = url_arg.as_ref().filter(|_| !relaunched) {
+        if config.single_window_per_url.eq_ignore_ascii_case("on")
+            && protocol::activate_instance_showing(url)
+        {
+            std::process::exit(0);
+        }
+    }
+    let launch_link = protocol_link.or(url_arg);
+
     // Single-instance enforcement (before any window is created)
-    if let Some(mode) = config.instance_mode() {
-        enforce_single_instance(mode);
+    if let Some(mode) = config.instance_mode().filter(|_| !relaunched && !selftest) {
+        enforce_single_instance(mode, config.kill_wait());
+    }
+
+    // Several instances: each holds a slot and keeps its own window state
+    if config.instance_mode().is_none() && !selftest {
+        AppConfig::set_instance_slot(claim_instance_slot(&config.app_user_model_id()));
     }
 
     // For multi-instance mode: count running siblings to compute cascade offset
-    // so each new instance opens at +32px from the previous one
-    let cascade_offset = if config.instance_mode().is_none() {
-        count_sibling_instances() as i32 * 32
+    // so each new instance opens one step (default +32,+32 px) from the previous one
+    let cascade_offset = if config.instance_mode().is_none() && !relaunched {
+        let siblings = count_sibling_instances() as i32;
+        let (step_x, step_y) = config.cascade_step();
+        (siblings * step_x, siblings * step_y)
     } else {
-        0
+        (0, 0)
     };
 
-    // Force dark mode: set the Chromium flag before WebView2 is created.
-    // This is the equivalent of Chrome's chrome://flags/#enable-force-dark-web-contents
-    // and will force-render all sites in dark mode even if they don't support it natively.
-    if config.force_dark_mode.eq_ignore_ascii_case("on") {
-        std::env::set_var(
-            "WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS",
-            "--enable-features=WebContentsForceDark",
-        );
+    // Chromium flags for WebView2 must be set before WebView2 is created
+    let browser_args = browser_args(&config);
+    if !browser_args.is_empty() {
+        std::env::set_var("WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS", browser_args);
+    }
+
+    // Offline app shell: the bundle_dir files are served as app://localhost/
+    let bundle_root = config.resolve_bundle_dir();
+    if !config.bundle_dir.trim().is_empty() && bundle_root.is_none() {
+        logging::log(&format!("bundle_dir {} not found", config.bundle_dir));
     }
 
     tauri::Builder::default()
+        .manage(config.clone())
+        .register_uri_scheme_protocol(bundle::SCHEME, move |_context, request| match &bundle_root {
+            Some(root) => bundle::respond(root, &request),
+            None => tauri::http::Response::builder()
+                .status(tauri::http::StatusCode::NOT_FOUND)
+                .body(Vec::new())
+                .unwrap_or_default(),
+        })
+        .invoke_handler(tauri::generate_handler![
+            commands::get_effective_config,
+            commands::navigate,
+            commands::reload,
+            commands::go_back,
+            commands::go_forward,
+        ])
         .setup(move |app| {
-            let window = app
-                .get_webview_window("main")
-                .expect("Failed to get main window");
+            // Per-exe user data folder (if configured) so differently-configured
+            // wrappers don't fight over one WebView2 profile. Portable mode keeps it
+            // beside the exe instead of in AppData.
+            let data_directory = if AppConfig::is_portable() {
+                AppConfig::portable_data_dir()
+            } else if config.per_exe_user_data() {
+                let exe_stem = AppConfig::exe_stem().unwrap_or_else(|| "app".to_string());
+                Some(app.path().app_local_data_dir()?.join(exe_stem))
+            } else {
+                None
+            };
+            if let Some(dir) = &data_directory {
+                remove_stale_retry_folders(dir);
+            }
+
+            // With defer_show, the window is created hidden and shown once geometry,
+            // title, icon and the first navigation are in place (no flash of a
+            // half-configured window)
+            let defer_show = config.defer_show.eq_ignore_ascii_case("on");
+            // With start_hidden, it stays hidden until the tray icon is clicked
+            let start_hidden = config.start_hidden.eq_ignore_ascii_case("on");
+            // tray_taskbar "keep": start minimized instead, so the taskbar button stays
+            let start_in_tray = start_hidden && !config.tray_keeps_taskbar();
+            let visible = !defer_show && !start_in_tray;
+
+            let created = create_main_window(app, &config, data_directory.clone(), visible);
+            let window = match created {
+                Ok(w) => w,
+                Err(e) => {
+                    logging::log(&format!("WebView2 environment creation failed: {}", e));
+                    // A crashed sibling can leave the per-exe user data folder locked.
+                    // Retry once in a fresh subfolder so this copy still loads.
+                    let Some(dir) = data_directory else {
+                        return Err(e.into());
+                    };
+                    let fresh = dir.join(format!("retry-{}", std::process::id()));
+                    logging::log(&format!(
+                        "Retrying with fresh user data folder: {}",
+                        fresh.display()
+                    ));
+                    create_main_window(app, &config, Some(fresh), visible).inspect_err(|e| {
+                        logging::log(&format!("WebView2 retry failed: {}", e));
+                    })?
+                }
+            };
+
+            // A `--layout <name>` from the command line applies for this run only and
+            // takes precedence over everything else
+            let mut placed = match config.launch_layout() {
+                Some(Ok(layout)) => {
+                    apply_layout(&window, layout);
+                    true
+                }
+                Some(Err(name)) => {
+                    logging::log(&format!("Unknown layout {:?}", name));
+                    false
+                }
+                None => false,
+            };
+
+            // A configured `geometry` pins the window to a monitor-relative layout and
+            // takes precedence over saved state. Fall back to saved state when it's
+            // malformed or its monitor isn't connected.
+            if !placed {
+                placed = match config.geometry() {
+                    Some(Ok(geometry)) => apply_geometry(&window, &geometry),
+                    Some(Err(spec)) => {
+                        logging::log(&format!("Ignoring invalid geometry: {:?}", spec));
+                        false
+                    }
+                    None => false,
+                };
+            }
+
+            // The HUD always opens centered
+            if !placed && config.hud() {
+                let _ = window.center();
+                placed = true;
+            }
 
             // Restore saved window position/size (with cascade offset for multi-instance)
-            restore_window_state(&window, cascade_offset);
+            if !placed {
+                restore_window_state(&window, &config, cascade_offset);
+            }
 
             // Set initial title from config (if provided)
             if !config.title.is_empty() {
@@ -58,41 +361,192 @@ pub fn run() {
             }
 
             // Set custom icon from config (if provided)
-            if let Some(icon_path) = config.resolve_icon_path() {
-                if let Ok(icon_data) = std::fs::read(&icon_path) {
-                    if let Ok(img) = tauri::image::Image::from_bytes(&icon_data) {
-                        let _ = window.set_icon(img);
-                    }
+            let icon = config.resolve_icon_path().and_then(|path| load_icon(&path));
+            if let Some(img) = icon.clone() {
+                let _ = window.set_icon(img);
+            }
+
+            let _ = MAIN_WINDOW.set(window.clone());
+
+            // Let the wrapped site call the diagnostics / navigation commands
+            if config.diagnostics_enabled() || config.navigation_commands_enabled() {
+                grant_page_ipc(app, &config)?;
+            }
+
+            // Tray icon to bring up the hidden window
+            if start_hidden {
+                setup_tray(app, &config)?;
+            }
+
+            // Add "About" item to the system menu (window icon menu). With system_menu off
+            // the window isn't subclassed, and the features handled there are skipped.
+            let subclassed = config.system_menu();
+            if subclassed {
+                setup_system_menu(&window, &config);
+            } else {
+                let skipped = config.subclass_features();
+                if !skipped.is_empty() {
+                    logging::log(&format!(
+                        "system_menu is off; without the window subclass these are limited: {}",
+                        skipped.join(", ")
+                    ));
                 }
             }
 
-            // Add "About" item to the system menu (window icon menu)
-            setup_system_menu(&window);
+            // Aero Snap and edge resizing for frameless windows
+            if config.frameless_snap() && subclassed {
+                setup_frameless_snap(&window);
+            }
+
+            // Page-declared drag regions (CSS `app-region: drag`)
+            if config.app_region.eq_ignore_ascii_case("on") && subclassed {
+                APP_REGION.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+
+            // Fixed-ratio content: keep the webview at the configured aspect ratio
+            if let Some(aspect) = config.content_aspect().filter(|_| subclassed) {
+                let _ = CONTENT_ASPECT.set(aspect);
+                letterbox_webview(&window);
+            }
+
+            // Hide the title bar while maximized, revealed at the top edge
+            if config.autohide_titlebar() && subclassed {
+                setup_autohide_titlebar(&window);
+            }
+
+            // Stable taskbar name while the title follows the page
+            let taskbar_title = config.taskbar_title.trim();
+            if !taskbar_title.is_empty() {
+                set_taskbar_title(&window, &config.app_user_model_id(), taskbar_title);
+            }
+
+            // Taskbar jump list tasks (if configured)
+            if config.jump_list.eq_ignore_ascii_case("on") {
+                setup_jump_list();
+            }
 
-            // Register WebView2 handlers (title sync + color scheme preference)
-            let title_window = window.clone();
-            let has_static_title = !config.title.is_empty();
-            let color_scheme = config.prefer_dark_mode.clone();
-            setup_webview_handlers(&window, title_window, has_static_title, &color_scheme);
+            // Register WebView2 handlers (title sync, color scheme preference, ...)
+            setup_webview_handlers(&window, &config);
 
             // Register window event handler to persist position/size
             let save_window = window.clone();
+            let save_config = config.clone();
+            // With alert_icon, focusing the window puts the normal icon back
+            let normal_icon = config
+                .resolve_alert_icon_path()
+                .and(icon.or_else(|| app.default_window_icon().map(|i| i.clone().to_owned())));
             window.on_window_event(move |event| {
                 use tauri::WindowEvent;
                 match event {
-                    WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
-                        save_window_state(&save_window);
+                    WindowEvent::Focused(focused) => {
+                        if let Some(icon) = normal_icon.clone().filter(|_| *focused) {
+                            let _ = save_window.set_icon(icon);
+                        }
+                        // throttle_background: slow the page down while another window
+                        // has the focus
+                        if save_config.throttle_background.eq_ignore_ascii_case("on") {
+                            throttle_page(&save_window, !*focused);
+                        }
+                    }
+                    // min_uptime_secs: keep running through a close that comes too early
+                    WindowEvent::CloseRequested { api, .. } if !quit_allowed(&save_config) => {
+                        api.prevent_close();
+                    }
+                    // file_drop "open": show the dropped file in the window. Only one page
+                    // can be shown, so of several files the first is opened.
+                    WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. })
+                        if save_config.file_drop() == "open" =>
+                    {
+                        if paths.len() > 1 {
+                            logging::log(&format!(
+                                "Dropped {} files: opening the first, ignoring the rest",
+                                paths.len()
+                            ));
+                        }
+                        let url = paths.first().and_then(|p| tauri::Url::from_file_path(p).ok());
+                        if let Some(url) = url {
+                            let _ = save_window.navigate(url);
+                        }
+                    }
+                    WindowEvent::Moved(_)
+                    | WindowEvent::Resized(_)
+                    | WindowEvent::ScaleFactorChanged { .. } => {
+                        save_window_state(&save_window, &save_config, false);
+                    }
+                    // CloseRequested: also when the taskbar's "Close all windows" closes
+                    // every instance at once, so each one writes its final state
+                    WindowEvent::CloseRequested { .. } => {
+                        save_window_state(&save_window, &save_config, true);
                     }
                     _ => {}
                 }
             });
 
-            // Navigate to the configured URL
-            let url: tauri::Url = config.url.parse().expect("Invalid URL in config.json");
-            let _ = window.navigate(url);
+            // Forget sessions on a wall-clock schedule (shared kiosks)
+            if let Some(schedule) = config.clear_schedule() {
+                start_clear_schedule(window.clone(), &config, schedule);
+            }
 
-            // Start minimized (if configured)
-            if config.start_minimized.eq_ignore_ascii_case("on") {
+            // Relaunch when a setting that only applies at WebView2 startup changes
+            if config.relaunch_on_config_change.eq_ignore_ascii_case("on") {
+                watch_config_for_relaunch(app.handle().clone(), &config);
+            }
+
+            // Navigate to the link (protocol or --url) we were launched with, the page
+            // of the active schedule slot, the last-visited page (resume_last_url) or
+            // the configured URL
+            let home: tauri::Url = config.url.parse().expect("Invalid URL in config.json");
+            let slots = config.schedule().unwrap_or_default();
+            let url = config
+                .resume_last_url
+                .eq_ignore_ascii_case("on")
+                .then(AppConfig::load_last_url)
+                .flatten()
+                .and_then(|last| last.parse::<tauri::Url>().ok())
+                .unwrap_or_else(|| home.clone());
+            let defer_navigation =
+                config.defer_navigation.eq_ignore_ascii_case("on") && launch_link.is_none();
+            let url = launch_link.or_else(|| scheduled_url(&slots)).unwrap_or(url);
+            // After an update, drop the cached assets of the old version first
+            let version = config.content_version();
+            if defer_navigation {
+                // Stay on the start page until a URL is pushed (`--url` or a protocol
+                // link, forwarded by a new instance)
+                logging::log("Navigation deferred: waiting for a URL");
+                NAVIGATION_DEFERRED.store(true, std::sync::atomic::Ordering::Relaxed);
+            } else if config.clear_cache_on_update.eq_ignore_ascii_case("on")
+                && AppConfig::load_version_marker().as_deref() != Some(version.as_str())
+            {
+                clear_cache_then_navigate(&window, url, version);
+            } else {
+                let _ = window.navigate(url);
+            }
+
+            // Window title from title_template, kept up to date with the connection state
+            if !config.title_template.trim().is_empty() {
+                update_title(&window);
+                if config.title_template.contains("{status}") {
+                    start_connectivity_watch(window.clone());
+                }
+            }
+
+            // Switch pages as schedule slots begin and end
+            if !slots.is_empty() {
+                start_schedule(window.clone(), home, slots);
+            }
+
+            // Reload a page that has grown past max_memory_mb (long-running kiosks)
+            if let Some(limit) = config.max_memory_mb() {
+                start_memory_guard(window.clone(), limit);
+            }
+
+            if defer_show && !start_in_tray {
+                let _ = window.show();
+            }
+
+            // Start minimized (if configured, or hidden to the tray with the taskbar kept)
+            let start_minimized = config.start_minimized.eq_ignore_ascii_case("on");
+            if (start_minimized && !start_hidden) || (start_hidden && !start_in_tray) {
                 let _ = window.minimize();
             }
 
@@ -102,482 +556,2609 @@ pub fn run() {
         .expect("error while running tauri application");
 }
 
-/// Restore window position, size, and maximized state from the saved state file.
-/// `cascade_offset` adds N pixels to both X and Y to cascade multiple instances
-/// so they don't stack exactly on top of each other (0 = no offset).
-fn restore_window_state(window: &tauri::WebviewWindow, cascade_offset: i32) {
-    if let Some(state) = WindowState::load() {
-        // Validate that the saved size is reasonable (at least 200x200)
-        if state.width >= 200 && state.height >= 200 {
-            let _ = window.set_size(tauri::PhysicalSize::new(state.width, state.height));
-        }
-        // Restore position with cascade offset
-        let _ = window.set_position(tauri::PhysicalPosition::new(
-            state.x + cascade_offset,
-            state.y + cascade_offset,
-        ));
-        // Restore maximized state
-        if state.maximized {
-            let _ = window.maximize();
-        }
-    } else if cascade_offset > 0 {
-        // No saved state (first run), but we have siblings — offset from default position
-        if let Ok(pos) = window.outer_position() {
-            let _ = window.set_position(tauri::PhysicalPosition::new(
-                pos.x + cascade_offset,
-                pos.y + cascade_offset,
-            ));
-        }
+/// Tray icon for `start_hidden`: a click (or "Show" in its menu) shows the window,
+/// "Quit" exits. It uses the configured `icon` when there is one.
+fn setup_tray(app: &tauri::App, config: &AppConfig) -> tauri::Result<()> {
+    use tauri::menu::{Menu, MenuItem};
+    use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+
+    let show = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&show, &quit])?;
+
+    let tooltip = if config.title.is_empty() {
+        AppConfig::exe_stem().unwrap_or_default()
+    } else {
+        config.title.clone()
+    };
+    let icon = config
+        .resolve_icon_path()
+        .and_then(|path| load_icon(&path))
+        .or_else(|| app.default_window_icon().cloned());
+
+    let mut builder = TrayIconBuilder::with_id("main")
+        .tooltip(tooltip)
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            "show" => show_main_window(),
+            "quit" if quit_allowed(&app.state::<AppConfig>()) => app.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|_tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window();
+            }
+        });
+    if let Some(icon) = icon {
+        builder = builder.icon(icon);
     }
+    builder.build(app)?;
+    Ok(())
 }
 
-/// Save current window position, size, and maximized state to disk
-fn save_window_state(window: &tauri::WebviewWindow) {
-    // When minimized, Windows moves the window to (-32000, -32000).
-    // Don't save that — we want to keep the last normal position.
-    if window.is_minimized().unwrap_or(false) {
-        return;
-    }
+/// Run the page's scripts at `BACKGROUND_THROTTLE_RATE` times slower, or at full speed
+#[cfg(target_os = "windows")]
+fn throttle_page(window: &tauri::WebviewWindow, throttled: bool) {
+    let rate = if throttled { BACKGROUND_THROTTLE_RATE } else { 1.0 };
+    let _ = window.with_webview(move |webview| unsafe {
+        if let Ok(core) = webview.controller().CoreWebView2() {
+            webview::set_cpu_throttling(&core, rate);
+        }
+    });
+}
 
-    let maximized = window.is_maximized().unwrap_or(false);
+#[cfg(not(target_os = "windows"))]
+fn throttle_page(_window: &tauri::WebviewWindow, _throttled: bool) {
+    // CPU throttling goes through the WebView2 DevTools Protocol; Windows-only
+}
 
-    // When maximized, don't overwrite the saved normal position/size —
-    // we want to restore the non-maximized geometry next time.
-    // Only save the maximized flag.
-    if maximized {
-        if let Some(mut state) = WindowState::load() {
-            state.maximized = true;
-            state.save();
-        } else {
-            // No previous state — save current dimensions with maximized flag
-            let pos = window.outer_position().unwrap_or_default();
-            let size = window.outer_size().unwrap_or_default();
-            let state = WindowState {
-                x: pos.x,
-                y: pos.y,
-                width: size.width,
-                height: size.height,
-                maximized: true,
-            };
-            state.save();
+/// Read an `.ico` or `.png` file as a window or tray icon
+pub(crate) fn load_icon(path: &std::path::Path) -> Option<tauri::image::Image<'static>> {
+    let data = std::fs::read(path).ok()?;
+    tauri::image::Image::from_bytes(&data).ok()
+}
+
+/// Show, restore and focus the main window (tray click). The HUD re-centers.
+fn show_main_window() {
+    if let Some(window) = MAIN_WINDOW.get() {
+        let config = window.state::<AppConfig>();
+        if config.hud() {
+            let _ = window.center();
         }
-        return;
+        // Bring the taskbar button back (hide_to_tray removed it) before showing, so
+        // the window never comes up without one
+        let _ = window.set_skip_taskbar(config.skip_taskbar.eq_ignore_ascii_case("on"));
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
     }
+}
 
-    let pos = window.outer_position().unwrap_or_default();
-    let size = window.outer_size().unwrap_or_default();
-    let state = WindowState {
-        x: pos.x,
-        y: pos.y,
-        width: size.width,
-        height: size.height,
-        maximized: false,
-    };
-    state.save();
+/// Hide the window to the tray. Its taskbar button is removed first (`tray_taskbar`
+/// "remove") so no stale button lingers; with "keep" it is minimized instead and the
+/// button and thumbnail stay.
+fn hide_to_tray(window: &tauri::WebviewWindow) {
+    if window.state::<AppConfig>().tray_keeps_taskbar() {
+        let _ = window.minimize();
+    } else {
+        let _ = window.set_skip_taskbar(true);
+        let _ = window.hide();
+    }
 }
 
-/// Count how many other processes with the same executable name are running.
-/// Used to compute the cascade offset for multi-instance window stacking.
-#[cfg(target_os = "windows")]
-fn count_sibling_instances() -> u32 {
-    use windows::Win32::System::Diagnostics::ToolHelp::{
-        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
-        TH32CS_SNAPPROCESS,
+/// Handle `--export-state` / `--import-state`. Returns the exit code when one was
+/// given: 0 on success, 1 when a file can't be read or written (or there is no
+/// saved state to export), 2 when the file to import isn't a valid window state.
+fn transfer_window_state() -> Option<i32> {
+    let fail = |code: i32, message: String| {
+        logging::log(&message);
+        eprintln!("{}", message);
+        code
     };
-    use windows::Win32::System::Threading::GetCurrentProcessId;
 
-    let our_pid = unsafe { GetCurrentProcessId() };
-    let our_exe = std::env::current_exe()
-        .ok()
-        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_lowercase().to_string()))
-        .unwrap_or_default();
+    if let Some(file) = config::arg_value("--export-state") {
+        let Some(state) = WindowState::load() else {
+            return Some(fail(1, "No saved window state to export".to_string()));
+        };
+        let json = serde_json::to_string_pretty(&state).unwrap_or_default();
+        return Some(match std::fs::write(&file, json) {
+            Ok(()) => 0,
+            Err(e) => fail(1, format!("Could not write {}: {}", file, e)),
+        });
+    }
 
-    if our_exe.is_empty() {
-        return 0;
+    if let Some(file) = config::arg_value("--import-state") {
+        let contents = match std::fs::read_to_string(&file) {
+            Ok(contents) => contents,
+            Err(e) => return Some(fail(1, format!("Could not read {}: {}", file, e))),
+        };
+        return Some(match serde_json::from_str::<WindowState>(&contents) {
+            Ok(state) => match state.try_save() {
+                Ok(()) => 0,
+                Err(e) => fail(1, format!("Could not save the imported window state: {}", e)),
+            },
+            Err(e) => fail(2, format!("{} is not a valid window state: {}", file, e)),
+        });
     }
 
-    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
-    let snapshot = match snapshot {
-        Ok(h) => h,
-        Err(_) => return 0,
-    };
+    None
+}
 
-    let mut entry = PROCESSENTRY32W::default();
-    entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
-    let mut count: u32 = 0;
+/// Chromium flags for WebView2, collected from all config options into one command
+/// line so no option overwrites another.
+fn browser_args(config: &AppConfig) -> String {
+    let mut args: Vec<String> = Vec::new();
 
-    unsafe {
-        if Process32FirstW(snapshot, &mut entry).is_ok() {
-            loop {
-                let name = String::from_utf16_lossy(
-                    &entry.szExeFile[..entry
-                        .szExeFile
-                        .iter()
-                        .position(|&c| c == 0)
-                        .unwrap_or(entry.szExeFile.len())],
-                );
+    // Force dark mode: the equivalent of Chrome's chrome://flags/#enable-force-dark-web-contents
+    // — force-renders all sites in dark mode even if they don't support it natively.
+    if let Some(feature) = config.force_dark_feature() {
+        args.push(format!("--enable-features={}", feature));
+    }
 
-                if name.to_lowercase() == our_exe && entry.th32ProcessID != our_pid {
-                    count += 1;
-                }
+    // Disable hardware acceleration — fixes rendering glitches on some VMs
+    if config.hardware_acceleration.eq_ignore_ascii_case("off") {
+        args.push("--disable-gpu --disable-gpu-compositing".to_string());
+    }
 
-                entry = PROCESSENTRY32W::default();
-                entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
-                if Process32NextW(snapshot, &mut entry).is_err() {
-                    break;
-                }
-            }
-        }
-        let _ = windows::Win32::Foundation::CloseHandle(snapshot);
+    // Browser language: the Accept-Language header sent to sites and the default
+    // locale for date and number formatting in scripts
+    if let Some(locale) = config.locale() {
+        args.push(format!("--lang={}", locale));
     }
 
-    count
-}
+    // Integrated Windows Authentication: let the site sign in with the current
+    // Windows account (Negotiate/NTLM) without a prompt. By default WebView2 only does
+    // that for hosts Windows puts in the Local Intranet zone.
+    if let Some(allowlist) = config.integrated_auth_allowlist() {
+        args.push(format!("--auth-server-allowlist={}", allowlist));
+    }
 
-#[cfg(not(target_os = "windows"))]
-fn count_sibling_instances() -> u32 {
-    0
+    // Autoplay allowlist: let media autoplay with sound, then pause it again on
+    // hosts outside `autoplay_hosts` (see `webview::register_autoplay_filter`)
+    if !config.autoplay_hosts.is_empty() {
+        args.push("--autoplay-policy=no-user-gesture-required".to_string());
+    }
+
+    // User-supplied escape hatch for flags not modelled by a config field
+    if !config.extra_browser_args.trim().is_empty() {
+        args.push(config.extra_browser_args.clone());
+    }
+
+    merge_browser_args(&args)
 }
 
-/// Bring the main window of the given process IDs to the foreground.
-/// Enumerates all top-level windows, finds one owned by one of the target PIDs,
-/// and uses ShowWindow + SetForegroundWindow to restore and activate it.
-#[cfg(target_os = "windows")]
-fn activate_process_windows(pids: &[u32]) {
-    use windows::Win32::Foundation::{HWND, LPARAM};
-    use windows::Win32::UI::WindowsAndMessaging::{
-        EnumWindows, GetWindowLongW, GetWindowTextLengthW, GetWindowThreadProcessId,
-        SetForegroundWindow, ShowWindow, GWL_STYLE, SW_RESTORE, SW_SHOW,
-        WS_VISIBLE, WS_MINIMIZE,
+/// Allow pages on the configured URL's origin to use Tauri IPC (commands) in the
+/// main window. Remote pages have no IPC access unless a capability grants it.
+fn grant_page_ipc(app: &tauri::App, config: &AppConfig) -> tauri::Result<()> {
+    let Some(origin) = config.origin_pattern() else {
+        return Ok(());
     };
+    app.add_capability(
+        tauri::ipc::CapabilityBuilder::new("page-ipc")
+            .remote(origin)
+            .window("main"),
+    )
+}
 
-    struct CallbackData {
-        pids: Vec<u32>,
-        found: HWND,
+/// Join browser argument strings into one command line. Chromium only honours the
+/// last `--enable-features` / `--disable-features` flag, so their values are
+/// combined into a single flag each instead of being repeated.
+fn merge_browser_args(args: &[String]) -> String {
+    let mut merged: Vec<String> = Vec::new();
+    let mut enable_features: Vec<String> = Vec::new();
+    let mut disable_features: Vec<String> = Vec::new();
+
+    for arg in args.iter().flat_map(|a| a.split_whitespace()) {
+        if let Some(features) = arg.strip_prefix("--enable-features=") {
+            enable_features.push(features.to_string());
+        } else if let Some(features) = arg.strip_prefix("--disable-features=") {
+            disable_features.push(features.to_string());
+        } else {
+            merged.push(arg.to_string());
+        }
     }
 
-    unsafe extern "system" fn enum_callback(hwnd: HWND, lparam: LPARAM) -> windows::core::BOOL {
-        let data = &mut *(lparam.0 as *mut CallbackData);
-        let mut pid: u32 = 0;
-        GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+    if !enable_features.is_empty() {
+        merged.push(format!("--enable-features={}", enable_features.join(",")));
+    }
+    if !disable_features.is_empty() {
+        merged.push(format!("--disable-features={}", disable_features.join(",")));
+    }
+    merged.join(" ")
+}
 
-        if data.pids.contains(&pid) {
-            // Check if this is a real app window (has a title bar text)
-            // This filters out invisible helper windows that processes often create
-            if GetWindowTextLengthW(hwnd) > 0 {
-                data.found = hwnd;
-                return windows::core::BOOL(0); // Stop enumerating
-            }
-        }
-        windows::core::BOOL(1) // Continue
+/// Create the main window. Built from code (not tauri.conf.json) so a WebView2
+/// environment failure surfaces as an error we can log and retry.
+/// A frameless window (`decorations: off`) keeps its drop shadow unless
+/// `window_shadow` is off; on Windows the shadow comes from extending the DWM frame
+/// 1px into the client area.
+fn create_main_window(
+    app: &tauri::App,
+    config: &AppConfig,
+    data_directory: Option<PathBuf>,
+    visible: bool,
+) -> tauri::Result<tauri::WebviewWindow> {
+    // The HUD preset is a small widget (a `geometry` or `--layout` can still size it)
+    let (width, height) = if config.hud() { (400.0, 300.0) } else { (1280.0, 800.0) };
+    let mut builder =
+        tauri::WebviewWindowBuilder::new(app, "main", tauri::WebviewUrl::default())
+            .title("TaWebMidi")
+            .inner_size(width, height)
+            .resizable(!config.resizable.eq_ignore_ascii_case("off"))
+            .decorations(config.decorations())
+            .shadow(config.window_shadow())
+            .always_on_top(config.always_on_top.eq_ignore_ascii_case("on"))
+            .skip_taskbar(config.skip_taskbar.eq_ignore_ascii_case("on"))
+            .transparent(config.transparent.eq_ignore_ascii_case("on"))
+            .visible(visible);
+    // file_drop "page": let drops through to the page's HTML5 handlers. Otherwise Tauri
+    // takes them, and they arrive as `DragDrop` window events (used by "open").
+    if config.file_drop() == "page" {
+        builder = builder.disable_drag_drop_handler();
+    }
+    if let Some(dir) = data_directory {
+        builder = builder.data_directory(dir);
     }
+    // Paint the window in the splash color until the page draws, instead of white.
+    // Letterbox bars show the window background, black unless splash_bg says otherwise.
+    let background = config.splash_bg().or(config.content_aspect().map(|_| (0, 0, 0)));
+    if let Some((r, g, b)) = background {
+        builder = builder.background_color(tauri::window::Color(r, g, b, 255));
+    }
+    // Fixed class name for automation tools (AutoHotkey, UI Automation) to target
+    let window_class = config.window_class.trim();
+    if !window_class.is_empty() {
+        builder = builder.window_classname(window_class);
+    }
+    builder.build()
+}
 
-    let mut data = CallbackData {
-        pids: pids.to_vec(),
-        found: HWND::default(),
+/// Size and position the window from a `geometry` spec, relative to the work area
+/// of the spec's monitor. Returns false if that monitor isn't connected.
+fn apply_geometry(window: &tauri::WebviewWindow, geometry: &Geometry) -> bool {
+    let Ok(monitors) = window.available_monitors() else {
+        return false;
+    };
+    let Some(monitor) = monitors.get(geometry.monitor - 1) else {
+        logging::log(&format!("Geometry monitor {} not found", geometry.monitor));
+        return false;
     };
 
-    unsafe {
-        let _ = EnumWindows(Some(enum_callback), LPARAM(&mut data as *mut _ as isize));
+    let area = monitor.work_area();
+    let width = geometry.width.resolve(area.size.width).max(1) as u32;
+    let height = geometry.height.resolve(area.size.height).max(1) as u32;
+    let x = area.position.x + geometry.x.resolve(area.size.width);
+    let y = area.position.y + geometry.y.resolve(area.size.height);
+
+    // The geometry is the whole window, but `set_size` sets the client area: take the
+    // frame and title bar off, so "100%" fits the work area instead of spilling over
+    let (frame_width, frame_height) = frame_size(window);
+    let _ = window.set_size(tauri::PhysicalSize::new(
+        width.saturating_sub(frame_width).max(1),
+        height.saturating_sub(frame_height).max(1),
+    ));
+    let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
+    true
+}
 
-        if data.found != HWND::default() {
-            // Check window style to determine if minimized
-            let style = GetWindowLongW(data.found, GWL_STYLE) as u32;
-            if style & WS_MINIMIZE.0 != 0 {
-                // Window is minimized — restore it
-                let _ = ShowWindow(data.found, SW_RESTORE);
-            } else if style & WS_VISIBLE.0 == 0 {
-                // Window exists but isn't visible — show it
-                let _ = ShowWindow(data.found, SW_SHOW);
-            }
-            let _ = SetForegroundWindow(data.found);
-        }
+/// Apply a named `--layout`. Only the fields the layout sets are changed.
+fn apply_layout(window: &tauri::WebviewWindow, layout: &Layout) {
+    if let (Some(width), Some(height)) = (layout.width, layout.height) {
+        let _ = window.set_size(tauri::PhysicalSize::new(width, height));
+    }
+    if let (Some(x), Some(y)) = (layout.x, layout.y) {
+        let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
+    }
+    if layout.maximized {
+        let _ = window.maximize();
     }
 }
 
-/// Enforce single-instance policy by checking for other processes with the same exe name.
-/// Mode "first": exit if another instance is already running.
-/// Mode "last": kill any existing instances, then continue.
+/// Restore window position, size, and maximized state from the saved state file.
+/// `cascade_offset` adds (X, Y) pixels to cascade multiple instances so they don't
+/// stack exactly on top of each other ((0, 0) = no offset).
+fn restore_window_state(
+    window: &tauri::WebviewWindow,
+    config: &AppConfig,
+    cascade_offset: (i32, i32),
+) {
+    // An instance with a state of its own reopens where it was; a further instance
+    // without one starts from the first instance's state, cascaded
+    let (state, cascade_offset) = match WindowState::load() {
+        Some(state) => (Some(state), (0, 0)),
+        None => (WindowState::load_first(), cascade_offset),
+    };
+    if let Some(state) = state {
+        // The size is rescaled to the DPI of the monitor the window reopens on, and
+        // the cascade wraps within that monitor using the restored size
+        let inset = frame_inset(window);
+        let (frame_x, frame_y) = frame_origin((state.x, state.y), inset);
+        let monitor = monitor_at(window, frame_x, frame_y);
+        let scale = match &monitor {
+            Some(monitor) => Some(monitor.scale_factor()),
+            None => window.scale_factor().ok(),
+        };
+        let size = restored_size(&state, scale, config.min_restore_size());
+        let outer = match size {
+            Some((width, height)) => {
+                let (frame_width, frame_height) = frame_size(window);
+                (width + frame_width, height + frame_height)
+            }
+            None => window.outer_size().map(|s| (s.width, s.height)).unwrap_or_default(),
+        };
+        let area = monitor.as_ref().map(work_area);
+        let (x, y) = restore_position((state.x, state.y), inset, cascade_offset, area, outer);
+        let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
+        // Size after positioning, so the window is on its saved monitor
+        if let Some((width, height)) = size {
+            let _ = window.set_size(tauri::PhysicalSize::new(width, height));
+        }
+        // Restore maximized state, unless configured to always reopen at the normal size
+        if state.maximized && !config.restore_maximized_as_normal() {
+            let _ = window.maximize();
+        }
+        // Reopen fullscreen on the monitor it was on
+        if let Some(name) = state.fullscreen_monitor.filter(|_| config.remember_fullscreen()) {
+            restore_fullscreen(window, &name);
+        }
+    } else if cascade_offset != (0, 0) {
+        // No saved state (first run), but we have siblings — offset from default position
+        if let Ok(pos) = window.outer_position() {
+            let (x, y) = cascade_position(window, pos.x, pos.y, cascade_offset);
+            let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
+        }
+    }
+}
+
+/// Inner size to restore from a saved state, in the basis `current_window_state` saves
+/// (client area), so it round-trips without creep. The saved size is in physical pixels
+/// at the saved scale; it is rescaled if the monitor's DPI has changed since. None when
+/// it is smaller than `min_size`.
+fn restored_size(
+    state: &WindowState,
+    current_scale: Option<f64>,
+    min_size: u32,
+) -> Option<(u32, u32)> {
+    if state.width < min_size || state.height < min_size {
+        return None;
+    }
+    let ratio = match current_scale {
+        Some(current) if state.scale_factor > 0.0 => current / state.scale_factor,
+        _ => 1.0,
+    };
+    Some((
+        (state.width as f64 * ratio).round() as u32,
+        (state.height as f64 * ratio).round() as u32,
+    ))
+}
+
+/// Move the window back onto the primary monitor if none of its title bar is on any
+/// connected monitor any more, e.g. after waking from sleep with a display unplugged.
+/// Maximized and fullscreen windows are left to the OS.
+#[cfg(target_os = "windows")]
+fn ensure_on_screen(window: &tauri::WebviewWindow) {
+    if window.is_maximized().unwrap_or(false) || window.is_fullscreen().unwrap_or(false) {
+        return;
+    }
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.outer_size()) else {
+        return;
+    };
+    let monitors = window.available_monitors().unwrap_or_default();
+    // The top 32 px of the window: enough of the title bar to grab it
+    let on_screen = monitors.iter().any(|monitor| {
+        let area = monitor.work_area();
+        position.x < area.position.x + area.size.width as i32
+            && position.x + size.width as i32 > area.position.x
+            && position.y < area.position.y + area.size.height as i32
+            && position.y + 32 > area.position.y
+    });
+    if on_screen || monitors.is_empty() {
+        return;
+    }
+
+    let Ok(Some(primary)) = window.primary_monitor() else { return };
+    let area = primary.work_area();
+    let x = area.position.x + (area.size.width as i32 - size.width as i32).max(0) / 2;
+    let y = area.position.y + (area.size.height as i32 - size.height as i32).max(0) / 2;
+    logging::log(&format!("Window was off-screen: moved to {},{}", x, y));
+    let _ = window.set_position(tauri::PhysicalPosition::new(x, y));
+}
+
+/// Go fullscreen on the monitor named `monitor_name`, or on the primary monitor when
+/// that one isn't connected any more
+fn restore_fullscreen(window: &tauri::WebviewWindow, monitor_name: &str) {
+    let monitor = window
+        .available_monitors()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|monitor| monitor.name().map(String::as_str) == Some(monitor_name))
+        .or_else(|| {
+            logging::log(&format!("Fullscreen monitor {} not found", monitor_name));
+            window.primary_monitor().ok().flatten()
+        });
+
+    // Fullscreen covers the monitor the window is on, so move it there first
+    if let Some(monitor) = monitor {
+        let current = window.current_monitor().ok().flatten();
+        if current.as_ref().and_then(|m| m.name()) != monitor.name() {
+            let area = monitor.work_area();
+            let _ = window.set_position(area.position);
+        }
+    }
+    let _ = window.set_fullscreen(true);
+}
+
+/// Apply the cascade offset to the window's current frame position, wrapped within
+/// the work area of the monitor it is on
+fn cascade_position(
+    window: &tauri::WebviewWindow,
+    x: i32,
+    y: i32,
+    offset: (i32, i32),
+) -> (i32, i32) {
+    let area = monitor_at(window, x, y).as_ref().map(work_area);
+    let size = window.outer_size().map(|s| (s.width, s.height)).unwrap_or_default();
+    cascade_in_area((x, y), offset, area, size)
+}
+
+/// The monitor containing the point, or the primary monitor when none does
+fn monitor_at(window: &tauri::WebviewWindow, x: i32, y: i32) -> Option<tauri::Monitor> {
+    window
+        .monitor_from_point(x as f64, y as f64)
+        .ok()
+        .flatten()
+        .or_else(|| window.primary_monitor().ok().flatten())
+}
+
+/// Position and size of a monitor's work area
+fn work_area(monitor: &tauri::Monitor) -> ((i32, i32), (u32, u32)) {
+    let area = monitor.work_area();
+    ((area.position.x, area.position.y), (area.size.width, area.size.height))
+}
+
+/// Frame position to restore a window to: the saved client-area origin `client` is
+/// converted to a frame origin using this window's decoration `inset`, so the cascade
+/// is exactly `offset` whether or not the window is decorated. `outer` is the frame
+/// size the window will have once restored.
+fn restore_position(
+    client: (i32, i32),
+    inset: (i32, i32),
+    offset: (i32, i32),
+    area: Option<((i32, i32), (u32, u32))>,
+    outer: (u32, u32),
+) -> (i32, i32) {
+    cascade_in_area(frame_origin(client, inset), offset, area, outer)
+}
+
+/// Apply the cascade offset to a frame position. If the result would leave the work
+/// `area` (position and size), wrap it around within that area so the Nth instance
+/// of an `outer`-sized window stays visible (negative steps wrap the other way).
+fn cascade_in_area(
+    pos: (i32, i32),
+    offset: (i32, i32),
+    area: Option<((i32, i32), (u32, u32))>,
+    outer: (u32, u32),
+) -> (i32, i32) {
+    let (x, y) = (pos.0 + offset.0, pos.1 + offset.1);
+    match area {
+        Some(((left, top), (width, height))) if offset != (0, 0) => (
+            wrap_into_area(x, left, width, outer.0),
+            wrap_into_area(y, top, height, outer.1),
+        ),
+        _ => (x, y),
+    }
+}
+
+/// Keep a window's top-left within `[start, start + extent - window_extent]` along one
+/// axis, wrapping around when a cascade runs past either end
+fn wrap_into_area(pos: i32, start: i32, extent: u32, window_extent: u32) -> i32 {
+    let span = (extent as i32 - window_extent as i32).max(1);
+    if pos >= start && pos <= start + span {
+        pos
+    } else {
+        start + (pos - start).rem_euclid(span)
+    }
+}
+
+/// Frame position that puts the client area at `client`, given the window's
+/// `frame_inset`
+fn frame_origin(client: (i32, i32), inset: (i32, i32)) -> (i32, i32) {
+    (client.0 - inset.0, client.1 - inset.1)
+}
+
+/// Extra width and height of the outer frame (borders, title bar) over the client area
+fn frame_size(window: &tauri::WebviewWindow) -> (u32, u32) {
+    match (window.outer_size(), window.inner_size()) {
+        (Ok(outer), Ok(inner)) => (
+            outer.width.saturating_sub(inner.width),
+            outer.height.saturating_sub(inner.height),
+        ),
+        _ => (0, 0),
+    }
+}
+
+/// Offset of the client area from the outer frame (title bar / border / invisible
+/// resize border). Zero-ish for frameless windows.
+fn frame_inset(window: &tauri::WebviewWindow) -> (i32, i32) {
+    match (window.inner_position(), window.outer_position()) {
+        (Ok(inner), Ok(outer)) => (inner.x - outer.x, inner.y - outer.y),
+        _ => (0, 0),
+    }
+}
+
+/// Save current window position, size, and maximized state to disk. `flush` is for
+/// the last save before the app goes away (close, relaunch, sign-out): a maximize is
+/// saved even within `maximize_save_delay`, since no later check will run.
+fn save_window_state(window: &tauri::WebviewWindow, config: &AppConfig, flush: bool) {
+    // When minimized, Windows moves the window to (-32000, -32000).
+    // Don't save that — we want to keep the last normal position.
+    if window.is_minimized().unwrap_or(false) {
+        return;
+    }
+
+    // A --layout arrangement is for this run only; keep the state normal launches restore
+    if matches!(config.launch_layout(), Some(Ok(_))) {
+        return;
+    }
+
+    // The HUD is always centered; keep the state a normal window would restore
+    if config.hud() {
+        return;
+    }
+
+    // Fullscreen (F11) covers the whole monitor; keep the geometry from before it.
+    // With remember_fullscreen, note the monitor so the next launch reopens there.
+    if window.is_fullscreen().unwrap_or(false) {
+        if config.remember_fullscreen() {
+            let monitor = window.current_monitor().ok().flatten();
+            let name = monitor.and_then(|monitor| monitor.name().cloned());
+            let mut state = WindowState::load().unwrap_or_default();
+            if state.fullscreen_monitor != name {
+                state.fullscreen_monitor = name;
+                state.save();
+            }
+        }
+        return;
+    }
+
+    static MAXIMIZED_SINCE: std::sync::Mutex<MaximizeDebounce> =
+        std::sync::Mutex::new(MaximizeDebounce::new());
+    static MAXIMIZE_CHECK_PENDING: std::sync::atomic::AtomicBool =
+        std::sync::atomic::AtomicBool::new(false);
+
+    let maximized = window.is_maximized().unwrap_or(false);
+    let delay = config.maximize_save_delay();
+    let now = std::time::Instant::now();
+    let wait = MAXIMIZED_SINCE.lock().unwrap().wait_left(maximized, now, delay);
+
+    // When maximized, don't overwrite the saved normal position/size —
+    // we want to restore the non-maximized geometry next time.
+    // Only save the maximized flag (always false with restore_maximized_as_normal,
+    // since the session then reopens un-maximized).
+    if let Some(wait) = wait {
+        // Only once it has stayed maximized for a moment: a quick maximize and restore
+        // (Snap Assist, a stray double-click) must not leave the flag behind. Check
+        // again when the delay is up; the window may have been restored by then.
+        if !wait.is_zero() && !flush {
+            use std::sync::atomic::Ordering;
+            if !MAXIMIZE_CHECK_PENDING.swap(true, Ordering::Relaxed) {
+                let window = window.clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(wait);
+                    MAXIMIZE_CHECK_PENDING.store(false, Ordering::Relaxed);
+                    save_window_state(&window, &window.state::<AppConfig>(), false);
+                });
+            }
+            return;
+        }
+
+        let reopen_maximized = !config.restore_maximized_as_normal();
+        if let Some(mut state) = WindowState::load() {
+            state.maximized = reopen_maximized;
+            state.fullscreen_monitor = None;
+            state.save();
+        } else {
+            // No previous state — save current dimensions with maximized flag
+            current_window_state(window, reopen_maximized).save();
+        }
+        return;
+    }
+
+    current_window_state(window, false).save();
+}
+
+/// Tracks how long the window has been maximized, for `maximize_save_delay`
+struct MaximizeDebounce {
+    since: Option<std::time::Instant>,
+}
+
+impl MaximizeDebounce {
+    const fn new() -> Self {
+        Self { since: None }
+    }
+
+    /// Note whether the window is maximized at `now`. While it is, returns how much
+    /// longer it has to stay maximized before the flag is saved (zero once `delay` has
+    /// passed); restoring it starts the wait over. None while it isn't maximized.
+    fn wait_left(
+        &mut self,
+        maximized: bool,
+        now: std::time::Instant,
+        delay: std::time::Duration,
+    ) -> Option<std::time::Duration> {
+        if !maximized {
+            self.since = None;
+            return None;
+        }
+        let since = *self.since.get_or_insert(now);
+        Some(delay.saturating_sub(now.saturating_duration_since(since)))
+    }
+}
+
+/// Snapshot the window geometry in the client-area basis `restore_window_state` uses.
+fn current_window_state(window: &tauri::WebviewWindow, maximized: bool) -> WindowState {
+    let pos = window.outer_position().map(|p| (p.x, p.y)).unwrap_or_default();
+    let size = window.outer_size().map(|s| (s.width, s.height)).unwrap_or_default();
+    let (x, y, width, height) =
+        client_geometry(pos, size, frame_inset(window), frame_size(window));
+    WindowState {
+        x,
+        y,
+        width,
+        height,
+        maximized,
+        scale_factor: window.scale_factor().unwrap_or(1.0),
+        fullscreen_monitor: None,
+    }
+}
+
+/// Client-area position and size of a window whose frame is at `pos` with size `size`,
+/// given its decoration `inset` and `frame` size. `set_size` sets the inner size and
+/// `restore_position` converts the client origin back to a frame position, so saving
+/// outer values would make the window creep by the decoration size on every launch
+/// (and differently on frameless windows).
+fn client_geometry(
+    pos: (i32, i32),
+    size: (u32, u32),
+    inset: (i32, i32),
+    frame: (u32, u32),
+) -> (i32, i32, u32, u32) {
+    (
+        pos.0 + inset.0,
+        pos.1 + inset.1,
+        size.0.saturating_sub(frame.0),
+        size.1.saturating_sub(frame.1),
+    )
+}
+
+/// Settings that are fixed once WebView2 has started: the Chromium flags and the
+/// user data folder. A change in any of them needs a process restart.
+fn startup_only_settings(config: &AppConfig) -> (String, bool, bool) {
+    let portable = config.portable.eq_ignore_ascii_case("on");
+    (browser_args(config), config.per_exe_user_data(), portable)
+}
+
+/// Poll the config file and relaunch the process when a startup-only setting
+/// (e.g. `force_dark_mode`) changes, since those can't be applied to a running
+/// WebView2. Other fields are left alone.
+fn watch_config_for_relaunch(app: tauri::AppHandle, config: &AppConfig) {
+    // Config from the environment or stdin can't change while we run
+    if AppConfig::config_is_inline() {
+        return;
+    }
+    let Ok(path) = AppConfig::find_config_path() else { return };
+    let startup_settings = startup_only_settings(config);
+    let modified = move || std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+
+    std::thread::spawn(move || {
+        let mut last_modified = modified();
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+            let current = modified();
+            if current == last_modified {
+                continue;
+            }
+            last_modified = current;
+
+            // The file may be caught mid-save; a parse error just waits for the next change
+            let Ok(new_config) = AppConfig::load() else { continue };
+            if startup_only_settings(&new_config) != startup_settings {
+                logging::log("Startup-only setting changed in config, relaunching");
+                relaunch(&app);
+                return;
+            }
+        }
+    });
+}
+
+/// Clear the configured browsing data on `schedule` from a background thread, then
+/// go back to the configured URL.
+#[cfg(target_os = "windows")]
+fn start_clear_schedule(window: tauri::WebviewWindow, config: &AppConfig, schedule: ClearSchedule) {
+    let kinds = config.clear_data();
+    let home = config.url.clone();
+
+    std::thread::spawn(move || {
+        // Local date of the last daily clear, so it runs once per day
+        let mut last_daily = None;
+        loop {
+            match schedule {
+                ClearSchedule::Every(interval) => std::thread::sleep(interval),
+                ClearSchedule::Daily { hour, minute } => {
+                    std::thread::sleep(std::time::Duration::from_secs(20));
+                    let now = unsafe { windows::Win32::System::SystemInformation::GetLocalTime() };
+                    let today = (now.wYear, now.wMonth, now.wDay);
+                    if (now.wHour, now.wMinute) != (hour, minute) || last_daily == Some(today) {
+                        continue;
+                    }
+                    last_daily = Some(today);
+                }
+            }
+
+            logging::log(&format!("Scheduled clear of browsing data: {}", kinds.join(", ")));
+            let kinds = kinds.clone();
+            let home = home.clone();
+            let _ = window.with_webview(move |webview| unsafe {
+                let Ok(core) = webview.controller().CoreWebView2() else { return };
+                let reload_core = core.clone();
+                webview::clear_browsing_data(&core, &kinds, move || {
+                    let _ = reload_core.Navigate(&windows::core::HSTRING::from(home));
+                });
+            });
+        }
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+fn start_clear_schedule(
+    _window: tauri::WebviewWindow,
+    _config: &AppConfig,
+    _schedule: ClearSchedule,
+) {
+    // Clearing browsing data uses WebView2 APIs; no-op on other platforms
+}
+
+/// `clear_cache_on_update`: clear the HTTP cache, Cache Storage and service workers
+/// (cookies and local storage stay), then remember `version` and load `url`
+#[cfg(target_os = "windows")]
+fn clear_cache_then_navigate(window: &tauri::WebviewWindow, url: tauri::Url, version: String) {
+    logging::log(&format!("Content version is now {}: clearing the cache", version));
+    let _ = window.with_webview(move |webview| unsafe {
+        let Ok(core) = webview.controller().CoreWebView2() else { return };
+        let kinds = ["cache", "cache_storage", "service_workers"].map(String::from);
+        let navigate_core = core.clone();
+        webview::clear_browsing_data(&core, &kinds, move || {
+            AppConfig::save_version_marker(&version);
+            let _ = navigate_core.Navigate(&windows::core::HSTRING::from(url.as_str()));
+        });
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+fn clear_cache_then_navigate(window: &tauri::WebviewWindow, url: tauri::Url, version: String) {
+    // No WebView2 cache to clear; just note the version
+    AppConfig::save_version_marker(&version);
+    let _ = window.navigate(url);
+}
+
+/// URL of the first `schedule` slot active at the current local time, if any
+#[cfg(target_os = "windows")]
+fn scheduled_url(slots: &[ScheduleSlot]) -> Option<tauri::Url> {
+    let now = unsafe { windows::Win32::System::SystemInformation::GetLocalTime() };
+    let minute = now.wHour * 60 + now.wMinute;
+    slots
+        .iter()
+        .find(|slot| slot.is_active(now.wDayOfWeek, minute))
+        .map(|slot| slot.url.clone())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn scheduled_url(_slots: &[ScheduleSlot]) -> Option<tauri::Url> {
+    // Local time comes from GetLocalTime; schedules are Windows-only
+    None
+}
+
+/// Navigate to the scheduled page whenever the active `schedule` slot changes, and back
+/// to `home` when a slot ends with no other one active. Checked every 20 seconds.
+#[cfg(target_os = "windows")]
+fn start_schedule(window: tauri::WebviewWindow, home: tauri::Url, slots: Vec<ScheduleSlot>) {
+    std::thread::spawn(move || {
+        let mut current = scheduled_url(&slots);
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(20));
+            let target = scheduled_url(&slots);
+            if target == current {
+                continue;
+            }
+            current = target;
+
+            let url = current.clone().unwrap_or_else(|| home.clone());
+            logging::log(&format!("Schedule: switching to {}", url));
+            let _ = window.navigate(url);
+        }
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+fn start_schedule(_window: tauri::WebviewWindow, _home: tauri::Url, _slots: Vec<ScheduleSlot>) {
+    // Local time comes from GetLocalTime; schedules are Windows-only
+}
+
+/// Set the window title from `title_template`: `{title}` is the page title and
+/// `{status}` is "online" or "offline"
+fn update_title(window: &tauri::WebviewWindow) {
+    let template = window.state::<AppConfig>().title_template.clone();
+    let online = ONLINE.load(std::sync::atomic::Ordering::Relaxed);
+    let title = template
+        .replace("{title}", &PAGE_TITLE.lock().unwrap())
+        .replace("{status}", if online { "online" } else { "offline" });
+    let _ = window.set_title(title.trim());
+}
+
+/// Update the title's `{status}` when the machine goes on- or offline, checked every
+/// 5 seconds through the Network List Manager
+#[cfg(target_os = "windows")]
+fn start_connectivity_watch(window: tauri::WebviewWindow) {
+    use windows::Win32::Networking::NetworkListManager::{
+        INetworkListManager, NetworkListManager,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL};
+    use windows::Win32::System::Com::COINIT_MULTITHREADED;
+
+    std::thread::spawn(move || unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let manager: INetworkListManager =
+            match CoCreateInstance(&NetworkListManager, None, CLSCTX_ALL) {
+                Ok(manager) => manager,
+                Err(e) => {
+                    logging::log(&format!("Connection status unavailable: {}", e));
+                    return;
+                }
+            };
+        loop {
+            if let Ok(connected) = manager.IsConnectedToInternet() {
+                let online = connected.as_bool();
+                if ONLINE.swap(online, std::sync::atomic::Ordering::Relaxed) != online {
+                    logging::log(if online { "Connection restored" } else { "Connection lost" });
+                    update_title(&window);
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_secs(5));
+        }
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+fn start_connectivity_watch(_window: tauri::WebviewWindow) {
+    // Connectivity comes from the Windows Network List Manager; {status} stays "online"
+}
+
+/// Check the webview's memory use every minute and reload the page once it exceeds
+/// `limit_mb`: as soon as nobody has touched the machine for a minute, or right away
+/// past one and a half times the limit. After a reload, checks pause for 10 minutes.
+#[cfg(target_os = "windows")]
+fn start_memory_guard(window: tauri::WebviewWindow, limit_mb: u64) {
+    use std::time::Duration;
+
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(60));
+
+        // The process list comes from COM on the webview's thread
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let _ = window.with_webview(move |webview| unsafe {
+            if let Ok(core) = webview.controller().CoreWebView2() {
+                let _ = sender.send(webview::memory_usage_mb(&core));
+            }
+        });
+        let Ok(Some(used_mb)) = receiver.recv_timeout(Duration::from_secs(10)) else {
+            continue;
+        };
+        if used_mb <= limit_mb || (used_mb < limit_mb * 3 / 2 && user_idle() < 60) {
+            continue;
+        }
+
+        logging::log(&format!(
+            "Memory use {} MB is over max_memory_mb ({} MB), reloading",
+            used_mb, limit_mb
+        ));
+        let _ = window.reload();
+        std::thread::sleep(Duration::from_secs(600));
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+fn start_memory_guard(_window: tauri::WebviewWindow, _limit_mb: u64) {
+    // Memory use comes from the WebView2 process list; the guard is Windows-only
+}
+
+/// Seconds since the last keyboard, mouse or touch input anywhere in the session
+#[cfg(target_os = "windows")]
+fn user_idle() -> u64 {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return 0;
+        }
+        (GetTickCount().wrapping_sub(info.dwTime) / 1000) as u64
+    }
+}
+
+/// Start a fresh copy of this process (same arguments plus `--relaunch <pid>`) and
+/// exit. Window geometry carries over through the saved window state.
+fn relaunch(app: &tauri::AppHandle) {
+    if let Some(window) = MAIN_WINDOW.get() {
+        save_window_state(window, &app.state::<AppConfig>(), true);
+    }
+
+    // A supervised child leaves the relaunch to its supervisor
+    if SUPERVISOR_PID.get().is_some() {
+        std::process::exit(SUPERVISED_RESTART_CODE);
+    }
+
+    let Ok(exe) = std::env::current_exe() else { return };
+    let args: Vec<String> = args_without_relaunch()
+        .into_iter()
+        .chain(["--relaunch".to_string(), std::process::id().to_string()])
+        .collect();
+
+    match std::process::Command::new(exe).args(args).spawn() {
+        Ok(_) => app.exit(0),
+        Err(e) => logging::log(&format!("Relaunch failed: {}", e)),
+    }
+}
+
+/// This process's command-line arguments without `--relaunch <pid>`
+fn args_without_relaunch() -> Vec<String> {
+    let mut args = Vec::new();
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--relaunch" {
+            iter.next();
+        } else {
+            args.push(arg);
+        }
+    }
+    args
+}
+
+/// Wait (up to `timeout`) for the process `pid` to exit
+#[cfg(target_os = "windows")]
+fn wait_for_exit(pid: u32, timeout: std::time::Duration) {
+    use windows::Win32::System::Threading::{OpenProcess, WaitForSingleObject, PROCESS_SYNCHRONIZE};
+
+    unsafe {
+        // Fails when the process is already gone, which is what we wait for
+        let Ok(handle) = OpenProcess(PROCESS_SYNCHRONIZE, false, pid) else { return };
+        WaitForSingleObject(handle, timeout.as_millis() as u32);
+        let _ = windows::Win32::Foundation::CloseHandle(handle);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn wait_for_exit(_pid: u32, _timeout: std::time::Duration) {
+    // The WebView2 profile lock is Windows-only; nothing to wait for
+}
+
+/// Release builds have no console of their own (`windows_subsystem = "windows"`), so
+/// `--selftest` prints to the console of the command prompt that started it, when
+/// there is one.
+#[cfg(target_os = "windows")]
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_parent_console() {
+    // Elsewhere the exe is a console program and prints to the terminal already
+}
+
+/// Whether a user's quit (close button, Alt+F4, tray **Quit**, exit gesture, ...) may
+/// end the app: not before it has run for `min_uptime_secs`, so a misconfigured
+/// autostart can't get stuck in a launch-and-quit loop
+fn quit_allowed(config: &AppConfig) -> bool {
+    let Some(min_uptime) = config.min_uptime() else { return true };
+    let uptime = STARTED.get().map(|started| started.elapsed()).unwrap_or(min_uptime);
+    if uptime >= min_uptime {
+        return true;
+    }
+    logging::log(&format!(
+        "Quit ignored: running for {} s of the required {} s",
+        uptime.as_secs(),
+        min_uptime.as_secs()
+    ));
+    false
+}
+
+/// Run the app as a child process and start it again whenever it exits with a non-zero
+/// code. Exit code 0 (quit from the window, tray or a shortcut) ends supervision; so does
+/// being terminated by a newer instance in single-instance `last` mode (`REPLACED_EXIT_CODE`).
+/// Children that crash right after starting are relaunched with a growing delay.
+fn supervise() -> i32 {
+    const MIN_DELAY: std::time::Duration = std::time::Duration::from_secs(2);
+    const MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+    const STABLE_RUN: std::time::Duration = std::time::Duration::from_secs(60);
+
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            logging::log(&format!("Supervisor: cannot locate own exe: {}", e));
+            return 1;
+        }
+    };
+    let args: Vec<String> = args_without_relaunch()
+        .into_iter()
+        .filter(|a| a != "--supervised")
+        .chain(["--supervised-child".to_string(), std::process::id().to_string()])
+        .collect();
+
+    let mut delay = MIN_DELAY;
+    loop {
+        let started = std::time::Instant::now();
+        let code = match std::process::Command::new(&exe).args(&args).status() {
+            Ok(status) => status.code(),
+            Err(e) => {
+                logging::log(&format!("Supervisor: failed to start the app: {}", e));
+                return 1;
+            }
+        };
+
+        match code {
+            Some(0) => return 0,
+            Some(REPLACED_EXIT_CODE) => {
+                logging::log("Supervisor: app was replaced by another instance, stopping");
+                return 0;
+            }
+            Some(SUPERVISED_RESTART_CODE) => {
+                delay = MIN_DELAY;
+                continue;
+            }
+            _ => {}
+        }
+
+        // Window position and size live in the state file, so the new child opens
+        // where the old one was
+        delay = if started.elapsed() >= STABLE_RUN {
+            MIN_DELAY
+        } else {
+            (delay * 2).min(MAX_DELAY)
+        };
+        logging::log(&format!(
+            "Supervisor: app exited unexpectedly ({}), relaunching in {:?}",
+            code.map_or("no exit code".to_string(), |c| format!("exit code {:#x}", c)),
+            delay
+        ));
+        std::thread::sleep(delay);
+    }
+}
+
+/// Count how many other processes with the same executable name are running.
+/// Used to compute the cascade offset for multi-instance window stacking.
+fn count_sibling_instances() -> u32 {
+    find_sibling_pids().len() as u32
+}
+
+/// Remove the `retry-<pid>` user data folders earlier runs fell back to when the
+/// profile was locked, except those of instances that are still running
+fn remove_stale_retry_folders(dir: &std::path::Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    let running = find_sibling_pids();
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let pid = name.to_str().and_then(|name| name.strip_prefix("retry-"));
+        let Some(pid) = pid.and_then(|pid| pid.parse::<u32>().ok()) else { continue };
+        if !running.contains(&pid) && std::fs::remove_dir_all(entry.path()).is_ok() {
+            logging::log(&format!("Removed stale user data folder: {}", entry.path().display()));
+        }
+    }
+}
+
+/// Claim the lowest free instance slot (1, 2, ...) with a named mutex held until the
+/// process exits, so a slot freed by a closed instance is taken over by the next one
+#[cfg(target_os = "windows")]
+fn claim_instance_slot(app_id: &str) -> u32 {
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ALREADY_EXISTS};
+    use windows::Win32::System::Threading::CreateMutexW;
+
+    for slot in 1..=MAX_INSTANCE_SLOTS {
+        let name = HSTRING::from(format!("Local\\{}.slot{}", app_id, slot));
+        unsafe {
+            let Ok(mutex) = CreateMutexW(None, false, &name) else { continue };
+            if GetLastError() == ERROR_ALREADY_EXISTS {
+                let _ = CloseHandle(mutex);
+                continue;
+            }
+        }
+        // The handle is left open on purpose: the slot is ours until we exit
+        return slot;
+    }
+    1
+}
+
+#[cfg(not(target_os = "windows"))]
+fn claim_instance_slot(_app_id: &str) -> u32 {
+    // Named mutexes are Windows-only; all instances share the first slot
+    1
+}
+
+/// PIDs of other running instances: processes with the same executable name as ours,
+/// except `--supervised` parents.
+#[cfg(target_os = "windows")]
+fn find_sibling_pids() -> Vec<u32> {
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+    use windows::Win32::System::Threading::GetCurrentProcessId;
+
+    let our_pid = unsafe { GetCurrentProcessId() };
+    let our_exe = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_lowercase().to_string()))
+        .unwrap_or_default();
+
+    if our_exe.is_empty() {
+        return Vec::new();
+    }
+
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+    let snapshot = match snapshot {
+        Ok(h) => h,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut entry = PROCESSENTRY32W::default();
+    entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+    // (pid, parent pid) of every process running our exe
+    let mut same_exe: Vec<(u32, u32)> = Vec::new();
+
+    unsafe {
+        if Process32FirstW(snapshot, &mut entry).is_ok() {
+            loop {
+                let name = String::from_utf16_lossy(
+                    &entry.szExeFile[..entry
+                        .szExeFile
+                        .iter()
+                        .position(|&c| c == 0)
+                        .unwrap_or(entry.szExeFile.len())],
+                );
+
+                if name.to_lowercase() == our_exe {
+                    same_exe.push((entry.th32ProcessID, entry.th32ParentProcessID));
+                }
+
+                entry = PROCESSENTRY32W::default();
+                entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+                if Process32NextW(snapshot, &mut entry).is_err() {
+                    break;
+                }
+            }
+        }
+        let _ = windows::Win32::Foundation::CloseHandle(snapshot);
+    }
+
+    // A process with our exe name that started another one is a `--supervised` parent
+    // (ours or another instance's) and not an instance itself
+    same_exe
+        .iter()
+        .map(|&(pid, _)| pid)
+        .filter(|&pid| pid != our_pid && Some(&pid) != SUPERVISOR_PID.get())
+        .filter(|&pid| !same_exe.iter().any(|&(_, parent)| parent == pid))
+        .collect()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn find_sibling_pids() -> Vec<u32> {
+    Vec::new()
+}
+
+/// Find the main window owned by one of the given process IDs.
+/// Enumerates all top-level windows and returns the first one owned by a target PID
+/// that has the configured `window_class`, or, without one, that has title bar text.
+#[cfg(target_os = "windows")]
+fn find_process_window(pids: &[u32]) -> Option<windows::Win32::Foundation::HWND> {
+    use windows::Win32::Foundation::{HWND, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetClassNameW, GetWindowTextLengthW, GetWindowThreadProcessId,
+    };
+
+    struct CallbackData {
+        pids: Vec<u32>,
+        class: Option<Vec<u16>>,
+        found: HWND,
+    }
+
+    unsafe extern "system" fn enum_callback(hwnd: HWND, lparam: LPARAM) -> windows::core::BOOL {
+        let data = &mut *(lparam.0 as *mut CallbackData);
+        let mut pid: u32 = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+
+        if data.pids.contains(&pid) {
+            let is_main_window = match &data.class {
+                // Only our own window class: helper windows never match
+                Some(class) => {
+                    let mut buffer = [0u16; 256];
+                    let len = GetClassNameW(hwnd, &mut buffer) as usize;
+                    buffer[..len] == class[..]
+                }
+                // Check if this is a real app window (has a title bar text)
+                // This filters out invisible helper windows that processes often create
+                None => GetWindowTextLengthW(hwnd) > 0,
+            };
+            if is_main_window {
+                data.found = hwnd;
+                return windows::core::BOOL(0); // Stop enumerating
+            }
+        }
+        windows::core::BOOL(1) // Continue
+    }
+
+    let mut data = CallbackData {
+        pids: pids.to_vec(),
+        class: WINDOW_CLASS.get().map(|class| class.encode_utf16().collect()),
+        found: HWND::default(),
+    };
+
+    unsafe {
+        let _ = EnumWindows(Some(enum_callback), LPARAM(&mut data as *mut _ as isize));
+    }
+
+    (data.found != HWND::default()).then_some(data.found)
+}
+
+/// Bring the main window of the given process IDs to the foreground.
+/// Uses ShowWindow + SetForegroundWindow to restore and activate it.
+/// Returns false if none of the processes has a window (yet).
+#[cfg(target_os = "windows")]
+fn activate_process_windows(pids: &[u32]) -> bool {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongW, SetForegroundWindow, ShowWindow, GWL_STYLE, SW_RESTORE, SW_SHOW,
+        WS_VISIBLE, WS_MINIMIZE,
+    };
+
+    let Some(hwnd) = find_process_window(pids) else { return false };
+
+    unsafe {
+        // Check window style to determine if minimized
+        let style = GetWindowLongW(hwnd, GWL_STYLE) as u32;
+        if style & WS_MINIMIZE.0 != 0 {
+            // Window is minimized — restore it
+            let _ = ShowWindow(hwnd, SW_RESTORE);
+        } else if style & WS_VISIBLE.0 == 0 {
+            // Window exists but isn't visible — show it
+            let _ = ShowWindow(hwnd, SW_SHOW);
+        }
+        let _ = SetForegroundWindow(hwnd);
+    }
+    true
+}
+
+/// Tell the user the app is already running when its window couldn't be brought
+/// forward, rather than exiting without any visible effect
+#[cfg(target_os = "windows")]
+fn show_already_running() {
+    use windows::Win32::UI::WindowsAndMessaging::MB_ICONINFORMATION;
+
+    let name = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "The app".to_string());
+    let message = format!(
+        "{} is already running, but its window could not be brought to the front.\n\n\
+         It may still be starting up, or be showing only in the system tray.",
+        name
+    );
+
+    show_message_box(&name, &message, MB_ICONINFORMATION);
+}
+
+/// Application icon resource id: tauri-build embeds the exe icon under the id of
+/// `IDI_APPLICATION`, and icon editors replace it in place
+#[cfg(target_os = "windows")]
+const APP_ICON_RESOURCE: u16 = 32512;
+
+/// Message box carrying the executable's own icon, so dialogs show the app's branding
+/// (including an icon swapped in with a resource editor). Falls back to a plain message
+/// box with `fallback_icon` when the exe has no icon resource.
+#[cfg(target_os = "windows")]
+fn show_message_box(
+    caption: &str,
+    text: &str,
+    fallback_icon: windows::Win32::UI::WindowsAndMessaging::MESSAGEBOX_STYLE,
+) {
+    use windows::Win32::Foundation::HINSTANCE;
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        LoadIconW, MessageBoxIndirectW, MessageBoxW, MB_OK, MB_USERICON, MSGBOXPARAMSW,
+    };
+
+    let caption: Vec<u16> = caption.encode_utf16().chain(std::iter::once(0)).collect();
+    let text: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let icon = windows::core::PCWSTR(APP_ICON_RESOURCE as usize as *const u16);
+
+    unsafe {
+        let instance = GetModuleHandleW(None).ok().map(|module| HINSTANCE(module.0));
+        if let Some(instance) = instance.filter(|&i| LoadIconW(Some(i), icon).is_ok()) {
+            let params = MSGBOXPARAMSW {
+                cbSize: std::mem::size_of::<MSGBOXPARAMSW>() as u32,
+                hInstance: instance,
+                lpszText: windows::core::PCWSTR(text.as_ptr()),
+                lpszCaption: windows::core::PCWSTR(caption.as_ptr()),
+                dwStyle: MB_OK | MB_USERICON,
+                lpszIcon: icon,
+                ..Default::default()
+            };
+            let _ = MessageBoxIndirectW(&params);
+            return;
+        }
+
+        let _ = MessageBoxW(
+            None,
+            windows::core::PCWSTR(text.as_ptr()),
+            windows::core::PCWSTR(caption.as_ptr()),
+            MB_OK | fallback_icon,
+        );
+    }
+}
+
+/// Enforce single-instance policy by checking for other processes with the same exe name.
+/// Mode "first": exit if another instance is already running.
+/// Mode "last": kill any existing instances and wait (up to `kill_wait`) until they
+/// have exited, then continue.
+#[cfg(target_os = "windows")]
+fn enforce_single_instance(mode: &str, kill_wait: std::time::Duration) {
+    use windows::Win32::System::Threading::{
+        OpenProcess, TerminateProcess, WaitForSingleObject, PROCESS_SYNCHRONIZE,
+        PROCESS_TERMINATE,
+    };
+
+    let found_pids = find_sibling_pids();
+
+    if found_pids.is_empty() {
+        return; // No other instance running, proceed normally
+    }
+
+    match mode {
+        "first" => {
+            // Another instance is already running — bring it to focus, then exit.
+            // It may still be starting and have no window yet, so keep trying briefly.
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(3);
+            while !activate_process_windows(&found_pids) {
+                if std::time::Instant::now() >= deadline {
+                    logging::log("Single instance: running instance has no window to focus");
+                    show_already_running();
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            std::process::exit(0);
+        }
+        "last" => {
+            // Kill all other instances, then wait until they are actually gone, so their
+            // window and WebView2 profile lock don't linger into this instance
+            let deadline = std::time::Instant::now() + kill_wait;
+            let mut handles = Vec::new();
+            for pid in found_pids {
+                unsafe {
+                    let access = PROCESS_TERMINATE | PROCESS_SYNCHRONIZE;
+                    if let Ok(handle) = OpenProcess(access, false, pid) {
+                        let _ = TerminateProcess(handle, REPLACED_EXIT_CODE as u32);
+                        handles.push(handle);
+                    }
+                }
+            }
+            for handle in handles {
+                let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                unsafe {
+                    WaitForSingleObject(handle, remaining.as_millis() as u32);
+                    let _ = windows::Win32::Foundation::CloseHandle(handle);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn enforce_single_instance(_mode: &str, _kill_wait: std::time::Duration) {
+    // Process enumeration is Windows-only; no-op on other platforms
+}
+
+#[cfg(target_os = "windows")]
+fn setup_webview_handlers(webview_window: &tauri::WebviewWindow, config: &AppConfig) {
+    let color_scheme = config.prefer_dark_mode.to_lowercase();
+    let needs_color_scheme = matches!(color_scheme.as_str(), "dark" | "light");
+    let has_static_title = !config.title.is_empty();
+    let has_template = !config.title_template.trim().is_empty();
+    let config = config.clone();
+    let window = webview_window.clone();
+
+    webview_window
+        .with_webview(move |webview| unsafe {
+            use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2;
+            use windows::core::Interface;
+
+            let controller = webview.controller();
+            let core: ICoreWebView2 = controller
+                .CoreWebView2()
+                .expect("Failed to get ICoreWebView2");
+
+            // Set preferred color scheme via ICoreWebView2Profile (requires v13+)
+            // "dark" = force dark preference, "light" = force light preference,
+            // anything else (including "" or "default") = let the OS decide
+            if needs_color_scheme {
+                use webview2_com::Microsoft::Web::WebView2::Win32::{
+                    ICoreWebView2_13, COREWEBVIEW2_PREFERRED_COLOR_SCHEME_DARK,
+                    COREWEBVIEW2_PREFERRED_COLOR_SCHEME_LIGHT,
+                };
+                let scheme = if color_scheme == "dark" {
+                    COREWEBVIEW2_PREFERRED_COLOR_SCHEME_DARK
+                } else {
+                    COREWEBVIEW2_PREFERRED_COLOR_SCHEME_LIGHT
+                };
+                if let Ok(core13) = core.cast::<ICoreWebView2_13>() {
+                    if let Ok(profile) = core13.Profile() {
+                        let _ = profile.SetPreferredColorScheme(scheme);
+                    }
+                }
+            }
+
+            // Register dynamic title sync (into title_template when one is set)
+            if !has_static_title || has_template {
+                use webview2_com::DocumentTitleChangedEventHandler;
+
+                let win = window.clone();
+                let handler = DocumentTitleChangedEventHandler::create(Box::new(
+                    move |webview, _args| {
+                        if let Some(wv) = webview {
+                            let mut title = windows::core::PWSTR::null();
+                            wv.DocumentTitle(&mut title)?;
+                            if !title.is_null() {
+                                let title_str = title.to_string().unwrap_or_default();
+                                if has_template {
+                                    *PAGE_TITLE.lock().unwrap() = title_str;
+                                    update_title(&win);
+                                } else {
+                                    let _ = win.set_title(&title_str);
+                                }
+                            }
+                        }
+                        Ok(())
+                    },
+                ));
+
+                let mut token: i64 = 0;
+                let _ = core.add_DocumentTitleChanged(&handler, &mut token);
+            }
+
+            // Configured shortcuts, F11 fullscreen toggle (can be disabled for kiosks) and
+            // Ctrl+Shift+R hard reload
+            webview::register_shortcut_keys(
+                &controller,
+                window.clone(),
+                !config.enable_fullscreen_key.eq_ignore_ascii_case("off"),
+                config.shortcuts().unwrap_or_default(),
+            );
+
+            // Page bridge (frameless window dragging, navigation state, ...)
+            bridge::register(&core, window.clone(), &config);
+
+            // Reduced motion / high contrast from the Windows settings
+            if config.accessibility.eq_ignore_ascii_case("on") {
+                webview::apply_accessibility_media(&core);
+                ACCESSIBILITY.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+
+            // Fresh page after the machine wakes from sleep
+            if config.reload_on_resume.eq_ignore_ascii_case("on") {
+                RELOAD_ON_RESUME.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+
+            // Time-relative content stays right after clock corrections
+            if config.reload_on_time_change.eq_ignore_ascii_case("on") {
+                RELOAD_ON_TIME_CHANGE.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+
+            // Settings toggles (zoom lock, ...)
+            webview::apply_settings(&core, &config);
+
+            // The page calling window.close(); "ignore" keeps a kiosk display up
+            match config.window_close_request() {
+                "ignore" => {}
+                action => webview::register_close_request(&core, window.clone(), action == "hide"),
+            }
+
+            // No permission prompts: requests are denied silently
+            if config.permission_prompts.eq_ignore_ascii_case("off") {
+                webview::register_permission_denial(&core);
+            }
+
+            // No alert/confirm/prompt or "leave this page?" dialogs
+            if config.script_dialogs.eq_ignore_ascii_case("off") {
+                webview::register_script_dialog_suppression(&core);
+            }
+
+            // No downloads
+            if config.downloads.eq_ignore_ascii_case("off") {
+                webview::register_download_blocking(&core);
+            }
+
+            // Text-only mode for metered connections
+            if config.load_images.eq_ignore_ascii_case("off") {
+                webview::register_image_blocking(&core);
+            }
+
+            // Block requests to ad/tracker hosts
+            if !config.block_hosts.is_empty() {
+                webview::register_host_blocking(&core, config.block_hosts.clone());
+            }
+
+            // Only trusted hosts may autoplay media
+            if !config.autoplay_hosts.is_empty() {
+                webview::register_autoplay_filter(&core, &config.autoplay_hosts);
+            }
+
+            // Fixed Referer for embedded widgets that check it
+            if !config.referer.is_empty() {
+                let referers = config.referer.clone().into_iter().collect();
+                webview::register_referer_override(&core, referers);
+            }
+
+            // Virtual-hosted development servers: another Host for the configured site
+            let host_header = config.host_header.trim();
+            if !host_header.is_empty() {
+                if let Ok(target) = config.url.parse::<tauri::Url>() {
+                    webview::register_host_header(&core, target, host_header.to_string());
+                }
+            }
+
+            // Same locale for scripts as for HTTP, even where the page asks explicitly
+            if let Some(locale) = config.locale() {
+                webview::register_locale_override(&core, locale);
+            }
+
+            // Larger (or smaller) text without zooming the layout
+            if let Some(scale) = config.font_scale() {
+                webview::register_font_scale(&core, scale);
+            }
+
+            // Session cookies exported from a browser, set before the first navigation
+            if let Some(path) = config.resolve_cookies_file() {
+                if let Ok(url) = config.url.parse::<tauri::Url>() {
+                    webview::import_cookies(&core, &path, &url);
+                }
+            }
+
+            // "Connection lost" banner over the page while offline
+            if config.connection_banner.eq_ignore_ascii_case("on") {
+                webview::register_connection_banner(&core);
+            }
+
+            // Spinner over the page while it loads
+            if config.loading_overlay.eq_ignore_ascii_case("on") {
+                webview::register_loading_overlay(&core, &config);
+            }
+
+            // Provisioned localStorage/sessionStorage entries for the configured site
+            if !config.local_storage.is_empty() || !config.session_storage.is_empty() {
+                if let Ok(url) = config.url.parse::<tauri::Url>() {
+                    let origin = url.origin().ascii_serialization();
+                    webview::register_storage_seed(&core, &origin, &config);
+                }
+            }
+
+            // Friendly error page for HTTP 4xx/5xx responses
+            if config.treat_http_errors.eq_ignore_ascii_case("on") {
+                webview::register_http_error_page(&core);
+            }
+
+            // Refuse pages served over an older TLS version than min_tls
+            if let Some(min_minor) = config.min_tls() {
+                webview::register_min_tls_check(&core, min_minor);
+            }
+
+            // Fail over between the `urls` targets, or retry an unreachable start page;
+            // either way, show the fallback page once nothing loads
+            let failover_urls = config.failover_urls();
+            if failover_urls.len() > 1 {
+                webview::register_failover(
+                    &core,
+                    window.clone(),
+                    failover_urls,
+                    config.fallback_url(),
+                );
+            } else if let Some(fallback) = config.fallback_url() {
+                webview::register_fallback(&core, window.clone(), fallback);
+            }
+
+            // Give up on navigations that hang and show the error page instead
+            if let Some(timeout) = config.navigation_timeout() {
+                let action = config.loading_timeout_action();
+                webview::register_navigation_timeout(&core, window.clone(), timeout, action);
+            }
+
+            // mailto:, tel:, ... links open in their system handler
+            let external_schemes = config.external_schemes();
+            if !external_schemes.is_empty() {
+                webview::register_external_schemes(&core, external_schemes);
+            }
+
+            // Let other instances ask which page this one shows
+            if config.single_window_per_url.eq_ignore_ascii_case("on") {
+                webview::register_current_url_tracking(&core);
+            }
+
+            // Remember the current page for the next launch
+            if config.resume_last_url.eq_ignore_ascii_case("on") {
+                webview::register_last_url_tracking(&core);
+            }
+
+            // Zoom: one level per site (not with a locked zoom), or default_zoom throughout
+            let zoom_locked = config.zoom_locked.eq_ignore_ascii_case("on");
+            if config.zoom_per_site.eq_ignore_ascii_case("on") && !zoom_locked {
+                webview::register_site_zoom(&controller, &core, config.default_zoom());
+            } else if config.default_zoom.is_some() {
+                let _ = controller.SetZoomFactor(config.default_zoom());
+            }
+
+            // Recent pages for the system menu
+            if config.recent_pages.eq_ignore_ascii_case("on") {
+                webview::register_history_tracking(&core);
+            }
+
+            // Match the window chrome to the page's <meta name="theme-color">
+            if config.use_theme_color.eq_ignore_ascii_case("on") {
+                webview::register_theme_color_sync(&core, window.clone());
+            }
+
+            // --selftest: report the first page load and exit
+            if SELFTEST.load(std::sync::atomic::Ordering::Relaxed) {
+                webview::register_selftest(&core);
+            }
+
+            // Remote debugging of a deployed wrapper: DevTools in their own window
+            if config.open_devtools.eq_ignore_ascii_case("on") && config.devtools() {
+                let _ = core.OpenDevToolsWindow();
+            }
+        })
+        .expect("Failed to access webview");
+}
+
+#[cfg(not(target_os = "windows"))]
+fn setup_webview_handlers(_webview_window: &tauri::WebviewWindow, _config: &AppConfig) {
+    // WebView2 APIs are Windows-only; color scheme and title sync are no-ops on other platforms
+}
+
+/// Show a native error dialog when the config file cannot be loaded.
+#[cfg(target_os = "windows")]
+fn show_config_error(error: &str) {
+    use windows::Win32::UI::WindowsAndMessaging::MB_ICONERROR;
+
+    let config_name = AppConfig::config_filename();
+    let message = format!(
+        "Could not load configuration file.\n\n\
+         Expected file: {}\n\
+         Place it next to the executable.\n\n\
+         Minimum required content:\n\n\
+         {{\n  \"url\": \"https://example.com\"\n}}\n\n\
+         Details: {}",
+        config_name, error
+    );
+
+    show_message_box("Configuration Error", &message, MB_ICONERROR);
+}
+
+#[cfg(not(target_os = "windows"))]
+fn show_config_error(error: &str) {
+    let config_name = AppConfig::config_filename();
+    eprintln!(
+        "Could not load configuration file.\n\n\
+         Expected file: {}\n\
+         Place it next to the executable.\n\n\
+         Minimum required content:\n\n\
+         {{\n  \"url\": \"https://example.com\"\n}}\n\n\
+         Details: {}",
+        config_name, error
+    );
+}
+
+/// Custom command ID for the "About" item in the system menu (window icon menu).
+/// Must be below 0xF000 and above standard SC_* values to avoid conflicts.
+#[cfg(target_os = "windows")]
+const SC_ABOUT: usize = 0x0010;
+
+/// Custom WM_SYSCOMMAND ID posted to a running instance by the "Reload" jump list task
+#[cfg(target_os = "windows")]
+const SC_RELOAD: usize = 0x0020;
+
+/// Custom command ID for the "Home page" system menu item (shown with resume_last_url)
+#[cfg(target_os = "windows")]
+const SC_HOME: usize = 0x0030;
+
+/// Custom command ID for the "Reload (ignore cache)" system menu item
+#[cfg(target_os = "windows")]
+const SC_HARD_RELOAD: usize = 0x0040;
+
+/// Custom command ID for the "Clear cookies for this site" system menu item
+/// (shown with diagnostics)
+#[cfg(target_os = "windows")]
+const SC_CLEAR_SITE_COOKIES: usize = 0x0050;
+
+/// Custom command ID of the first "Recent pages" submenu item (shown with recent_pages);
+/// item `i` is `SC_RECENT_PAGE + i * 0x10`, since Windows uses the low 4 bits
+#[cfg(target_os = "windows")]
+const SC_RECENT_PAGE: usize = 0x0100;
+
+/// The "Recent pages" submenu, refilled from the history file each time it opens
+#[cfg(target_os = "windows")]
+static RECENT_MENU: std::sync::atomic::AtomicIsize = std::sync::atomic::AtomicIsize::new(0);
+
+/// Add a custom "Tauri WebApp on Demand vX.Y.Z" item to the window's system menu
+/// along with "Reload (ignore cache)" (plus "Home page" when resume_last_url is on,
+/// "Clear cookies for this site" with diagnostics and "Recent pages" with recent_pages),
+/// and subclass the window to handle clicks on them.
+#[cfg(target_os = "windows")]
+fn setup_system_menu(window: &tauri::WebviewWindow, config: &AppConfig) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        AppendMenuW, CreatePopupMenu, GetSystemMenu, MF_POPUP, MF_SEPARATOR, MF_STRING,
+    };
+    use windows::Win32::UI::Shell::SetWindowSubclass;
+
+    let Ok(hwnd) = window.hwnd() else { return };
+    let hwnd = HWND(hwnd.0 as *mut _);
+
+    unsafe {
+        let hmenu = GetSystemMenu(hwnd, false);
+        if hmenu.is_invalid() {
+            return;
+        }
+
+        // Add separator + about item
+        let _ = AppendMenuW(hmenu, MF_SEPARATOR, 0, None);
+        let label: Vec<u16> = format!("Tauri WebApp on Demand v{}", APP_VERSION)
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let _ = AppendMenuW(
+            hmenu,
+            MF_STRING,
+            SC_ABOUT,
+            windows::core::PCWSTR(label.as_ptr()),
+        );
+
+        let label: Vec<u16> = "Reload (ignore cache)\tCtrl+Shift+R\0".encode_utf16().collect();
+        let _ = AppendMenuW(
+            hmenu,
+            MF_STRING,
+            SC_HARD_RELOAD,
+            windows::core::PCWSTR(label.as_ptr()),
+        );
+
+        // Resuming the last page needs a way back to the configured start page
+        if config.resume_last_url.eq_ignore_ascii_case("on") {
+            let label: Vec<u16> = "Home page\0".encode_utf16().collect();
+            let _ = AppendMenuW(
+                hmenu,
+                MF_STRING,
+                SC_HOME,
+                windows::core::PCWSTR(label.as_ptr()),
+            );
+        }
+
+        // Testing login flows: start over on the current site only
+        if config.diagnostics_enabled() {
+            let label: Vec<u16> = "Clear cookies for this site\0".encode_utf16().collect();
+            let _ = AppendMenuW(
+                hmenu,
+                MF_STRING,
+                SC_CLEAR_SITE_COOKIES,
+                windows::core::PCWSTR(label.as_ptr()),
+            );
+        }
+
+        // Pages from this and earlier sessions, since the back/forward history is lost
+        if config.recent_pages.eq_ignore_ascii_case("on") {
+            if let Ok(submenu) = CreatePopupMenu() {
+                let label: Vec<u16> = "Recent pages\0".encode_utf16().collect();
+                let _ = AppendMenuW(
+                    hmenu,
+                    MF_POPUP,
+                    submenu.0 as usize,
+                    windows::core::PCWSTR(label.as_ptr()),
+                );
+                RECENT_MENU.store(submenu.0 as isize, std::sync::atomic::Ordering::Relaxed);
+                fill_recent_menu(submenu);
+            }
+        }
+
+        // Subclass to intercept WM_SYSCOMMAND for our custom menu items
+        let _ = SetWindowSubclass(hwnd, Some(sysmenu_subclass_proc), 1, 0);
+    }
+}
+
+/// Replace the items of the "Recent pages" submenu with the current history
+#[cfg(target_os = "windows")]
+unsafe fn fill_recent_menu(submenu: windows::Win32::UI::WindowsAndMessaging::HMENU) {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        AppendMenuW, DeleteMenu, GetMenuItemCount, MF_BYPOSITION, MF_GRAYED, MF_STRING,
+    };
+
+    for _ in 0..GetMenuItemCount(Some(submenu)).max(0) {
+        let _ = DeleteMenu(submenu, 0, MF_BYPOSITION);
+    }
+
+    let history = AppConfig::load_history();
+    if history.is_empty() {
+        let label: Vec<u16> = "(none yet)\0".encode_utf16().collect();
+        let _ = AppendMenuW(
+            submenu,
+            MF_STRING | MF_GRAYED,
+            0,
+            windows::core::PCWSTR(label.as_ptr()),
+        );
+    }
+    for (index, url) in history.iter().enumerate() {
+        // `&` would mark a mnemonic; long URLs are shortened to keep the menu narrow
+        let mut text: String = url.replace('&', "&&").chars().take(80).collect();
+        if url.chars().count() > 80 {
+            text.push('…');
+        }
+        let label: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+        let _ = AppendMenuW(
+            submenu,
+            MF_STRING,
+            SC_RECENT_PAGE + index * 0x10,
+            windows::core::PCWSTR(label.as_ptr()),
+        );
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn sysmenu_subclass_proc(
+    hwnd: windows::Win32::Foundation::HWND,
+    umsg: u32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+    _uidsubclass: usize,
+    _dwrefdata: usize,
+) -> windows::Win32::Foundation::LRESULT {
+    use windows::Win32::UI::Shell::DefSubclassProc;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        HTCAPTION, HTCLIENT, PBT_APMRESUMEAUTOMATIC, WM_COPYDATA, WM_DPICHANGED,
+        WM_ENDSESSION, WM_INITMENUPOPUP, WM_NCHITTEST, WM_POWERBROADCAST, WM_SETTINGCHANGE,
+        WM_SIZE, WM_SYSCOMMAND, WM_TIMECHANGE, WM_TIMER,
+    };
+
+    if umsg == WM_SYSCOMMAND && wparam.0 == SC_ABOUT {
+        // Open the project URL in the default browser
+        open_in_browser(APP_REPO_URL);
+        return windows::Win32::Foundation::LRESULT(0);
+    }
+
+    if umsg == WM_SYSCOMMAND && wparam.0 == SC_CLEAR_SITE_COOKIES {
+        if let Some(window) = MAIN_WINDOW.get() {
+            let _ = window.with_webview(|webview| {
+                if let Ok(core) = webview.controller().CoreWebView2() {
+                    webview::clear_site_cookies(&core);
+                }
+            });
+        }
+        return windows::Win32::Foundation::LRESULT(0);
+    }
+
+    let recent_pages = SC_RECENT_PAGE..SC_RECENT_PAGE + config::MAX_HISTORY * 0x10;
+    if umsg == WM_SYSCOMMAND && recent_pages.contains(&(wparam.0 & 0xFFF0)) {
+        let index = ((wparam.0 & 0xFFF0) - SC_RECENT_PAGE) / 0x10;
+        let url = AppConfig::load_history().into_iter().nth(index);
+        if let (Some(window), Some(Ok(url))) = (MAIN_WINDOW.get(), url.map(|u| u.parse())) {
+            let _ = window.navigate(url);
+        }
+        return windows::Win32::Foundation::LRESULT(0);
+    }
+
+    if umsg == WM_INITMENUPOPUP {
+        let submenu = RECENT_MENU.load(std::sync::atomic::Ordering::Relaxed);
+        if submenu != 0 && wparam.0 as isize == submenu {
+            fill_recent_menu(windows::Win32::UI::WindowsAndMessaging::HMENU(submenu as *mut _));
+        }
+    }
+
+    if umsg == WM_SYSCOMMAND && wparam.0 == SC_RELOAD {
+        if let Some(window) = MAIN_WINDOW.get() {
+            let _ = window.reload();
+        }
+        return windows::Win32::Foundation::LRESULT(0);
+    }
+
+    if umsg == WM_SYSCOMMAND && wparam.0 == SC_HARD_RELOAD {
+        if let Some(window) = MAIN_WINDOW.get() {
+            let _ = window.with_webview(|webview| {
+                if let Ok(core) = webview.controller().CoreWebView2() {
+                    webview::hard_reload(&core);
+                }
+            });
+        }
+        return windows::Win32::Foundation::LRESULT(0);
+    }
+
+    if umsg == WM_SYSCOMMAND && wparam.0 == SC_HOME {
+        // Back to the configured URL; forget the stored page so it isn't resumed
+        if let Some(window) = MAIN_WINDOW.get() {
+            AppConfig::clear_last_url();
+            let config = window.state::<AppConfig>();
+            if let Ok(url) = config.url.parse::<tauri::Url>() {
+                let _ = window.navigate(url);
+            }
+        }
+        return windows::Win32::Foundation::LRESULT(0);
+    }
+
+    let frameless_snap = FRAMELESS_SNAP.load(std::sync::atomic::Ordering::Relaxed);
+    let app_region = APP_REGION.load(std::sync::atomic::Ordering::Relaxed);
+    if umsg == WM_NCHITTEST && (frameless_snap || app_region) {
+        let hit = DefSubclassProc(hwnd, umsg, wparam, lparam);
+        if hit.0 == HTCLIENT as isize {
+            if frameless_snap {
+                if let Some(edge) = resize_edge_hit(hwnd, lparam) {
+                    return windows::Win32::Foundation::LRESULT(edge as isize);
+                }
+            }
+            // The webview covers the client area and only lets hit tests through to us
+            // over CSS `app-region: drag` areas, which act as the title bar
+            if app_region {
+                return windows::Win32::Foundation::LRESULT(HTCAPTION as isize);
+            }
+        }
+        return hit;
+    }
+
+    if umsg == WM_SETTINGCHANGE && ACCESSIBILITY.load(std::sync::atomic::Ordering::Relaxed) {
+        // Animation or contrast settings may have changed
+        if let Some(window) = MAIN_WINDOW.get() {
+            let _ = window.with_webview(|webview| {
+                if let Ok(core) = webview.controller().CoreWebView2() {
+                    webview::apply_accessibility_media(&core);
+                }
+            });
+        }
+    }
+
+    if umsg == WM_ENDSESSION && wparam.0 != 0 {
+        // Sign-out or shutdown: the process is ended without a close request
+        if let Some(window) = MAIN_WINDOW.get() {
+            save_window_state(window, &window.state::<AppConfig>(), true);
+        }
+    }
+
+    if umsg == WM_POWERBROADCAST && wparam.0 == PBT_APMRESUMEAUTOMATIC as usize {
+        // Back from sleep: a display may be gone, and the page may be stale or frozen
+        if let Some(window) = MAIN_WINDOW.get() {
+            let window = window.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(RESUME_SETTLE_TIME);
+                ensure_on_screen(&window);
+                if RELOAD_ON_RESUME.load(std::sync::atomic::Ordering::Relaxed) {
+                    logging::log("Resumed from sleep: reloading");
+                    let _ = window.reload();
+                }
+            });
+        }
+    }
+
+    if umsg == WM_TIMECHANGE && RELOAD_ON_TIME_CHANGE.load(std::sync::atomic::Ordering::Relaxed) {
+        // Clock set or time zone changed. A sync can change the time several times in
+        // a row, so reload once, a moment after the first change.
+        static RELOAD_PENDING: std::sync::atomic::AtomicBool =
+            std::sync::atomic::AtomicBool::new(false);
+        if let Some(window) = MAIN_WINDOW.get() {
+            if !RELOAD_PENDING.swap(true, std::sync::atomic::Ordering::Relaxed) {
+                let window = window.clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(std::time::Duration::from_secs(2));
+                    RELOAD_PENDING.store(false, std::sync::atomic::Ordering::Relaxed);
+                    logging::log("System time changed: reloading");
+                    let _ = window.reload();
+                });
+            }
+        }
+    }
+
+    if umsg == WM_TIMER && wparam.0 == AUTOHIDE_TIMER_ID {
+        update_autohide_titlebar(hwnd);
+        return windows::Win32::Foundation::LRESULT(0);
+    }
+
+    if umsg == WM_COPYDATA {
+        let config = MAIN_WINDOW.get().map(|window| window.state::<AppConfig>());
+        match config.and_then(|config| protocol::handle_copy_data(lparam, &config)) {
+            // A link forwarded by a newly launched instance
+            Some(protocol::CopyData::Navigate(url)) => {
+                if let Some(window) = MAIN_WINDOW.get() {
+                    let _ = window.navigate(url);
+                    // defer_navigation with start_hidden: appear once there is a page
+                    let deferred =
+                        NAVIGATION_DEFERRED.swap(false, std::sync::atomic::Ordering::Relaxed);
+                    if deferred && !window.is_visible().unwrap_or(true) {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                    }
+                }
+                return windows::Win32::Foundation::LRESULT(1);
+            }
+            // single_window_per_url: a new instance asks whether we show its URL
+            Some(protocol::CopyData::ShowsUrl(shows)) => {
+                return windows::Win32::Foundation::LRESULT(shows as isize);
+            }
+            None => {}
+        }
+    }
+
+    if umsg == WM_SIZE && CONTENT_ASPECT.get().is_some() {
+        // The default handling stretches the webview over the client area; shrink it back
+        let result = DefSubclassProc(hwnd, umsg, wparam, lparam);
+        if let Some(window) = MAIN_WINDOW.get() {
+            letterbox_webview(window);
+        }
+        return result;
+    }
+
+    if umsg == WM_DPICHANGED {
+        // Let the default handling run first, then make sure the window ends up at
+        // Windows' suggested rectangle for the new DPI and the webview re-rasterizes
+        // at the new scale (otherwise content can stay blurry until reload)
+        let result = DefSubclassProc(hwnd, umsg, wparam, lparam);
+        handle_dpi_changed(hwnd, wparam, lparam);
+        return result;
+    }
+
+    DefSubclassProc(hwnd, umsg, wparam, lparam)
+}
+
+/// Size the webview to the largest `content_aspect` rectangle that fits the client area,
+/// centered, leaving bars of window background around it instead of stretching it.
+#[cfg(target_os = "windows")]
+fn letterbox_webview(window: &tauri::WebviewWindow) {
+    use windows::Win32::Foundation::{HWND, RECT};
+    use windows::Win32::UI::WindowsAndMessaging::GetClientRect;
+
+    let Some(&aspect) = CONTENT_ASPECT.get() else { return };
+    let Ok(hwnd) = window.hwnd() else { return };
+    let mut client = RECT::default();
+    if unsafe { GetClientRect(HWND(hwnd.0 as *mut _), &mut client) }.is_err() {
+        return;
+    }
+
+    let (width, height) = (client.right as f64, client.bottom as f64);
+    if width <= 0.0 || height <= 0.0 {
+        return;
+    }
+    let (content_width, content_height) = if width / height > aspect {
+        (height * aspect, height)
+    } else {
+        (width, width / aspect)
+    };
+    let left = ((width - content_width) / 2.0).round() as i32;
+    let top = ((height - content_height) / 2.0).round() as i32;
+    let bounds = RECT {
+        left,
+        top,
+        right: left + content_width.round() as i32,
+        bottom: top + content_height.round() as i32,
+    };
+
+    // Runs after the pending resize, so it overrides the stretch to the full client area
+    let _ = window.with_webview(move |webview| unsafe {
+        let _ = webview.controller().SetBounds(bounds);
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+fn letterbox_webview(_window: &tauri::WebviewWindow) {
+    // The webview fills the window on other platforms
+}
+
+/// Apply the WM_DPICHANGED suggested rectangle and the new rasterization scale.
+/// The saved window state picks up the new scale via the ScaleFactorChanged event.
+#[cfg(target_os = "windows")]
+unsafe fn handle_dpi_changed(
+    hwnd: windows::Win32::Foundation::HWND,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+) {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::UI::WindowsAndMessaging::{SetWindowPos, SWP_NOACTIVATE, SWP_NOZORDER};
+
+    // LOWORD(wParam) = new X DPI; lParam = suggested window rectangle
+    let dpi = (wparam.0 & 0xFFFF) as f64;
+    if let Some(rect) = (lparam.0 as *const RECT).as_ref() {
+        let _ = SetWindowPos(
+            hwnd,
+            None,
+            rect.left,
+            rect.top,
+            rect.right - rect.left,
+            rect.bottom - rect.top,
+            SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+    }
+
+    if let Some(window) = MAIN_WINDOW.get() {
+        let _ = window.with_webview(move |webview| unsafe {
+            use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Controller3;
+            use windows::core::Interface;
+
+            if let Ok(controller) = webview.controller().cast::<ICoreWebView2Controller3>() {
+                let _ = controller.SetRasterizationScale(dpi / 96.0);
+            }
+        });
+    }
+}
+
+/// Make a frameless window behave like a normal one for Aero Snap: keep the resizable
+/// frame and maximize/minimize styles Windows checks before snapping, and report
+/// resize borders from WM_NCHITTEST (see `resize_edge_hit`). Dragging comes from the
+/// page bridge, which starts Windows' own caption drag.
 #[cfg(target_os = "windows")]
-fn enforce_single_instance(mode: &str) {
-    use windows::Win32::System::Diagnostics::ToolHelp::{
-        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
-        TH32CS_SNAPPROCESS,
-    };
-    use windows::Win32::System::Threading::{
-        GetCurrentProcessId, OpenProcess, TerminateProcess, PROCESS_TERMINATE,
+fn setup_frameless_snap(window: &tauri::WebviewWindow) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetWindowLongPtrW, SetWindowPos, GWL_STYLE, SWP_FRAMECHANGED,
+        SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, WS_MAXIMIZEBOX, WS_MINIMIZEBOX,
+        WS_THICKFRAME,
     };
 
-    // Get our own exe name and PID
-    let our_pid = unsafe { GetCurrentProcessId() };
-    let our_exe = std::env::current_exe()
-        .ok()
-        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_lowercase().to_string()))
-        .unwrap_or_default();
+    let Ok(hwnd) = window.hwnd() else { return };
+    let hwnd = HWND(hwnd.0 as *mut _);
 
-    if our_exe.is_empty() {
-        return;
+    unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_STYLE);
+        let snap_styles = (WS_THICKFRAME | WS_MAXIMIZEBOX | WS_MINIMIZEBOX).0 as isize;
+        SetWindowLongPtrW(hwnd, GWL_STYLE, style | snap_styles);
+        let _ = SetWindowPos(
+            hwnd,
+            None,
+            0,
+            0,
+            0,
+            0,
+            SWP_FRAMECHANGED | SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+        );
     }
+    FRAMELESS_SNAP.store(true, std::sync::atomic::Ordering::Relaxed);
+}
 
-    // Snapshot all processes
-    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
-    let snapshot = match snapshot {
-        Ok(h) => h,
-        Err(_) => return,
+/// Resize border hit test for a frameless window: the HT* edge code when the cursor
+/// (screen coordinates in `lparam`) is within the system resize border, else None.
+/// Maximized windows have no resize border.
+#[cfg(target_os = "windows")]
+unsafe fn resize_edge_hit(
+    hwnd: windows::Win32::Foundation::HWND,
+    lparam: windows::Win32::Foundation::LPARAM,
+) -> Option<u32> {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetSystemMetrics, GetWindowRect, IsZoomed, HTBOTTOM, HTBOTTOMLEFT, HTBOTTOMRIGHT,
+        HTLEFT, HTRIGHT, HTTOP, HTTOPLEFT, HTTOPRIGHT, SM_CXPADDEDBORDER, SM_CXSIZEFRAME,
+        SM_CYSIZEFRAME,
     };
 
-    let mut entry = PROCESSENTRY32W::default();
-    entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+    if IsZoomed(hwnd).as_bool() {
+        return None;
+    }
+    let mut rect = RECT::default();
+    GetWindowRect(hwnd, &mut rect).ok()?;
+
+    // Signed 16-bit screen coordinates (negative on monitors left of / above the primary)
+    let x = (lparam.0 & 0xFFFF) as i16 as i32;
+    let y = ((lparam.0 >> 16) & 0xFFFF) as i16 as i32;
+    let padding = GetSystemMetrics(SM_CXPADDEDBORDER);
+    let border_x = GetSystemMetrics(SM_CXSIZEFRAME) + padding;
+    let border_y = GetSystemMetrics(SM_CYSIZEFRAME) + padding;
+
+    let left = x < rect.left + border_x;
+    let right = x >= rect.right - border_x;
+    let top = y < rect.top + border_y;
+    let bottom = y >= rect.bottom - border_y;
+
+    match (left, right, top, bottom) {
+        (true, _, true, _) => Some(HTTOPLEFT),
+        (_, true, true, _) => Some(HTTOPRIGHT),
+        (true, _, _, true) => Some(HTBOTTOMLEFT),
+        (_, true, _, true) => Some(HTBOTTOMRIGHT),
+        (true, _, _, _) => Some(HTLEFT),
+        (_, true, _, _) => Some(HTRIGHT),
+        (_, _, true, _) => Some(HTTOP),
+        (_, _, _, true) => Some(HTBOTTOM),
+        _ => None,
+    }
+}
+
+/// Timer ID for the `autohide_titlebar` cursor check in the subclass proc
+#[cfg(target_os = "windows")]
+const AUTOHIDE_TIMER_ID: usize = 0x4154; // "AT"
 
-    let mut found_pids: Vec<u32> = Vec::new();
+/// Start polling the cursor for `autohide_titlebar`. The webview covers the whole
+/// client area, so the window never sees mouse moves itself; a timer handled in the
+/// subclass proc (`update_autohide_titlebar`) checks the cursor instead.
+#[cfg(target_os = "windows")]
+fn setup_autohide_titlebar(window: &tauri::WebviewWindow) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::SetTimer;
+
+    let Ok(hwnd) = window.hwnd() else { return };
+    let hwnd = HWND(hwnd.0 as *mut _);
 
     unsafe {
-        if Process32FirstW(snapshot, &mut entry).is_ok() {
-            loop {
-                let name = String::from_utf16_lossy(
-                    &entry.szExeFile[..entry
-                        .szExeFile
-                        .iter()
-                        .position(|&c| c == 0)
-                        .unwrap_or(entry.szExeFile.len())],
-                );
+        SetTimer(Some(hwnd), AUTOHIDE_TIMER_ID, 150, None);
+    }
+}
 
-                if name.to_lowercase() == our_exe && entry.th32ProcessID != our_pid {
-                    found_pids.push(entry.th32ProcessID);
-                }
+/// While maximized, hide the title bar and show it again while the cursor is at the
+/// top edge of the monitor (until it moves below the title bar). Outside maximized
+/// state the title bar is always shown.
+#[cfg(target_os = "windows")]
+unsafe fn update_autohide_titlebar(hwnd: windows::Win32::Foundation::HWND) {
+    use std::sync::atomic::Ordering;
+    use windows::Win32::Foundation::POINT;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetCursorPos, GetSystemMetrics, IsZoomed, SM_CXPADDEDBORDER, SM_CYCAPTION,
+        SM_CYSIZEFRAME,
+    };
 
-                entry = PROCESSENTRY32W::default();
-                entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
-                if Process32NextW(snapshot, &mut entry).is_err() {
-                    break;
-                }
-            }
+    let Some(window) = MAIN_WINDOW.get() else { return };
+    let hidden = TITLEBAR_HIDDEN.load(Ordering::Relaxed);
+
+    if !IsZoomed(hwnd).as_bool() {
+        if hidden {
+            TITLEBAR_HIDDEN.store(false, Ordering::Relaxed);
+            let _ = window.set_decorations(true);
         }
-        let _ = windows::Win32::Foundation::CloseHandle(snapshot);
+        return;
     }
 
-    if found_pids.is_empty() {
-        return; // No other instance running, proceed normally
+    let Ok(Some(monitor)) = window.current_monitor() else { return };
+    let mut cursor = POINT::default();
+    if GetCursorPos(&mut cursor).is_err() {
+        return;
     }
-
-    match mode {
-        "first" => {
-            // Another instance is already running — bring it to focus, then exit
-            activate_process_windows(&found_pids);
-            std::process::exit(0);
-        }
-        "last" => {
-            // Kill all other instances, then continue
-            for pid in found_pids {
-                unsafe {
-                    if let Ok(handle) = OpenProcess(PROCESS_TERMINATE, false, pid) {
-                        let _ = TerminateProcess(handle, 1);
-                        let _ = windows::Win32::Foundation::CloseHandle(handle);
-                    }
-                }
-            }
-            // Brief sleep to let the OS clean up the terminated processes
-            std::thread::sleep(std::time::Duration::from_millis(200));
-        }
-        _ => {}
+    let top = monitor.work_area().position.y;
+    let caption = GetSystemMetrics(SM_CYCAPTION)
+        + GetSystemMetrics(SM_CYSIZEFRAME)
+        + GetSystemMetrics(SM_CXPADDEDBORDER);
+
+    if hidden && cursor.y <= top + 1 {
+        TITLEBAR_HIDDEN.store(false, Ordering::Relaxed);
+        let _ = window.set_decorations(true);
+    } else if !hidden && cursor.y > top + caption {
+        TITLEBAR_HIDDEN.store(true, Ordering::Relaxed);
+        let _ = window.set_decorations(false);
     }
 }
 
 #[cfg(not(target_os = "windows"))]
-fn enforce_single_instance(_mode: &str) {
-    // Process enumeration is Windows-only; no-op on other platforms
+fn setup_autohide_titlebar(_window: &tauri::WebviewWindow) {
+    // Title bar auto-hide is Windows-only
+}
+
+#[cfg(not(target_os = "windows"))]
+fn setup_frameless_snap(_window: &tauri::WebviewWindow) {
+    // Aero Snap is Windows-only
+}
+
+#[cfg(not(target_os = "windows"))]
+fn setup_system_menu(_window: &tauri::WebviewWindow, _config: &AppConfig) {
+    // System menu customization is Windows-only
 }
 
+/// Set the process-wide AppUserModelID, which Windows uses to group taskbar buttons,
+/// match pinned shortcuts and jump lists, and attribute notifications.
 #[cfg(target_os = "windows")]
-fn setup_webview_handlers(
-    webview_window: &tauri::WebviewWindow,
-    title_window: tauri::WebviewWindow,
-    has_static_title: bool,
-    color_scheme: &str,
-) {
-    let needs_color_scheme = matches!(color_scheme.to_lowercase().as_str(), "dark" | "light");
+fn set_app_user_model_id(id: &str) {
+    use windows::core::HSTRING;
+    use windows::Win32::UI::Shell::SetCurrentProcessExplicitAppUserModelID;
 
-    // Nothing to do if no dynamic title and no color scheme override
-    if has_static_title && !needs_color_scheme {
-        return;
+    if let Err(e) = unsafe { SetCurrentProcessExplicitAppUserModelID(&HSTRING::from(id)) } {
+        logging::log(&format!("Failed to set AppUserModelID {:?}: {}", id, e));
     }
+}
 
-    let color_scheme = color_scheme.to_lowercase();
-    webview_window
-        .with_webview(move |webview| unsafe {
-            use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2;
-            use windows::core::Interface;
+#[cfg(not(target_os = "windows"))]
+fn set_app_user_model_id(_id: &str) {
+    // AppUserModelIDs are a Windows shell concept
+}
 
-            let controller = webview.controller();
-            let core: ICoreWebView2 = controller
-                .CoreWebView2()
-                .expect("Failed to get ICoreWebView2");
+/// Give the window's taskbar button a fixed name (`taskbar_title`) through its shell
+/// properties: the AppUserModelID the button belongs to, and the display name and
+/// command used for it. Shown on the taskbar group, on the pinned button and in the
+/// group's jump list header, while the window text (title bar, button label with
+/// "never combine", Alt+Tab) keeps following the page.
+#[cfg(target_os = "windows")]
+fn set_taskbar_title(window: &tauri::WebviewWindow, app_id: &str, title: &str) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Storage::EnhancedStorage::{
+        PKEY_AppUserModel_ID, PKEY_AppUserModel_RelaunchCommand,
+        PKEY_AppUserModel_RelaunchDisplayNameResource,
+    };
+    use windows::Win32::System::Com::StructuredStorage::PROPVARIANT;
+    use windows::Win32::UI::Shell::PropertiesSystem::{
+        IPropertyStore, SHGetPropertyStoreForWindow,
+    };
 
-            // Set preferred color scheme via ICoreWebView2Profile (requires v13+)
-            // "dark" = force dark preference, "light" = force light preference,
-            // anything else (including "" or "default") = let the OS decide
-            if needs_color_scheme {
-                use webview2_com::Microsoft::Web::WebView2::Win32::{
-                    ICoreWebView2_13, COREWEBVIEW2_PREFERRED_COLOR_SCHEME_DARK,
-                    COREWEBVIEW2_PREFERRED_COLOR_SCHEME_LIGHT,
-                };
-                let scheme = if color_scheme == "dark" {
-                    COREWEBVIEW2_PREFERRED_COLOR_SCHEME_DARK
-                } else {
-                    COREWEBVIEW2_PREFERRED_COLOR_SCHEME_LIGHT
-                };
-                if let Ok(core13) = core.cast::<ICoreWebView2_13>() {
-                    if let Ok(profile) = core13.Profile() {
-                        let _ = profile.SetPreferredColorScheme(scheme);
-                    }
-                }
+    let Ok(hwnd) = window.hwnd() else { return };
+    let Ok(exe) = std::env::current_exe() else { return };
+    let command = format!("\"{}\"", exe.display());
+
+    // The shell only reads the relaunch properties together with an ID
+    let properties = [
+        (PKEY_AppUserModel_ID, app_id),
+        (PKEY_AppUserModel_RelaunchCommand, command.as_str()),
+        (PKEY_AppUserModel_RelaunchDisplayNameResource, title),
+    ];
+    let result = unsafe {
+        SHGetPropertyStoreForWindow::<IPropertyStore>(HWND(hwnd.0 as *mut _)).and_then(|store| {
+            for (key, value) in &properties {
+                store.SetValue(key, &PROPVARIANT::from(*value))?;
             }
+            store.Commit()
+        })
+    };
+    if let Err(e) = result {
+        logging::log(&format!("Failed to set taskbar title {:?}: {}", title, e));
+    }
+}
 
-            // Register dynamic title sync
-            if !has_static_title {
-                use webview2_com::DocumentTitleChangedEventHandler;
+#[cfg(not(target_os = "windows"))]
+fn set_taskbar_title(_window: &tauri::WebviewWindow, _app_id: &str, _title: &str) {
+    // Taskbar button properties are a Windows shell concept
+}
 
-                let win = title_window.clone();
-                let handler = DocumentTitleChangedEventHandler::create(Box::new(
-                    move |webview, _args| {
-                        if let Some(wv) = webview {
-                            let mut title = windows::core::PWSTR::null();
-                            wv.DocumentTitle(&mut title)?;
-                            if !title.is_null() {
-                                let title_str = title.to_string().unwrap_or_default();
-                                let _ = win.set_title(&title_str);
-                            }
-                        }
-                        Ok(())
-                    },
-                ));
+/// Apply `process_priority`: "above" (above normal) or "high". Anything else, including
+/// "realtime", leaves the normal priority — realtime can starve the rest of the system.
+#[cfg(target_os = "windows")]
+fn set_process_priority(priority: &str) {
+    use windows::Win32::System::Threading::{
+        GetCurrentProcess, SetPriorityClass, ABOVE_NORMAL_PRIORITY_CLASS, HIGH_PRIORITY_CLASS,
+    };
 
-                let mut token: i64 = 0;
-                let _ = core.add_DocumentTitleChanged(&handler, &mut token);
-            }
-        })
-        .expect("Failed to access webview");
+    let class = match priority.trim().to_ascii_lowercase().as_str() {
+        "above" => ABOVE_NORMAL_PRIORITY_CLASS,
+        "high" => HIGH_PRIORITY_CLASS,
+        _ => return,
+    };
+    if let Err(e) = unsafe { SetPriorityClass(GetCurrentProcess(), class) } {
+        logging::log(&format!("Failed to set process priority {:?}: {}", priority, e));
+    }
 }
 
 #[cfg(not(target_os = "windows"))]
-fn setup_webview_handlers(
-    _webview_window: &tauri::WebviewWindow,
-    _title_window: tauri::WebviewWindow,
-    _has_static_title: bool,
-    _color_scheme: &str,
-) {
-    // WebView2 APIs are Windows-only; color scheme and title sync are no-ops on other platforms
+fn set_process_priority(_priority: &str) {
+    // Priority classes are Windows-only; use `nice` elsewhere
 }
 
-/// Show a native error dialog when the config file cannot be loaded.
+/// Open a URL in the default browser
 #[cfg(target_os = "windows")]
-fn show_config_error(_error: &str) {
-    use windows::Win32::UI::WindowsAndMessaging::{MessageBoxW, MB_OK, MB_ICONERROR};
+fn open_in_browser(url: &str) {
+    use std::os::windows::process::CommandExt;
+    let _ = std::process::Command::new("cmd")
+        .args(["/C", "start", "", url])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .spawn();
+}
 
-    let config_name = AppConfig::config_filename();
-    let message = format!(
-        "Could not load configuration file.\n\n\
-         Expected file: {}\n\
-         Place it next to the executable.\n\n\
-         Minimum required content:\n\n\
-         {{\n  \"url\": \"https://example.com\"\n}}",
-        config_name
-    );
+/// Register taskbar jump list tasks: "New Window" (launches another instance),
+/// "Reload" (reloads the running instance) and "Open Repo". Each task relaunches
+/// this exe, with a flag handled by `handle_jump_list_task` for the last two.
+#[cfg(target_os = "windows")]
+fn setup_jump_list() {
+    use windows::core::{Interface, HSTRING};
+    use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+    use windows::Win32::System::Com::StructuredStorage::PROPVARIANT;
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+    };
+    use windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+    use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+    use windows::Win32::UI::Shell::{
+        DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW,
+        ShellLink,
+    };
 
-    let caption: Vec<u16> = "Configuration Error\0".encode_utf16().collect();
-    let text: Vec<u16> = message.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe fn build_list(exe: &HSTRING) -> windows::core::Result<()> {
+        // Already initialized on the main thread by the webview; this is a no-op then
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let list: ICustomDestinationList =
+            CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+        let mut max_slots: u32 = 0;
+        let _removed: IObjectArray = list.BeginList(&mut max_slots)?;
+
+        let tasks: IObjectCollection =
+            CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+        for (title, args) in [
+            ("New Window", ""),
+            ("Reload", "--reload"),
+            ("Open Repo", "--open-repo"),
+        ] {
+            let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+            link.SetPath(exe)?;
+            link.SetArguments(&HSTRING::from(args))?;
+            link.SetIconLocation(exe, 0)?;
+
+            // Jump list tasks take their label from the link's title property
+            let store: IPropertyStore = link.cast()?;
+            store.SetValue(&PKEY_Title, &PROPVARIANT::from(title))?;
+            store.Commit()?;
+
+            tasks.AddObject(&link)?;
+        }
 
-    unsafe {
-        let _ = MessageBoxW(
-            None,
-            windows::core::PCWSTR(text.as_ptr()),
-            windows::core::PCWSTR(caption.as_ptr()),
-            MB_OK | MB_ICONERROR,
-        );
+        list.AddUserTasks(&tasks.cast::<IObjectArray>()?)?;
+        list.CommitList()
+    }
+
+    let Ok(exe) = std::env::current_exe() else { return };
+    let exe = HSTRING::from(exe.to_string_lossy().as_ref());
+
+    if let Err(e) = unsafe { build_list(&exe) } {
+        logging::log(&format!("Failed to register jump list: {}", e));
     }
 }
 
 #[cfg(not(target_os = "windows"))]
-fn show_config_error(_error: &str) {
-    let config_name = AppConfig::config_filename();
-    eprintln!(
-        "Could not load configuration file.\n\n\
-         Expected file: {}\n\
-         Place it next to the executable.\n\n\
-         Minimum required content:\n\n\
-         {{\n  \"url\": \"https://example.com\"\n}}",
-        config_name
-    );
+fn setup_jump_list() {
+    // Jump lists are a Windows taskbar feature
 }
 
-/// Custom command ID for the "About" item in the system menu (window icon menu).
-/// Must be below 0xF000 and above standard SC_* values to avoid conflicts.
+/// Handle a jump list task flag in a freshly launched process.
+/// Returns true if the flag was a task (the process should exit afterwards).
 #[cfg(target_os = "windows")]
-const SC_ABOUT: usize = 0x0010;
+fn handle_jump_list_task(task: &str) -> bool {
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{PostMessageW, WM_SYSCOMMAND};
+
+    match task {
+        "--open-repo" => {
+            open_in_browser(APP_REPO_URL);
+            true
+        }
+        "--reload" => {
+            // Ask the running instance to reload via its system-menu subclass proc
+            if let Some(hwnd) = find_process_window(&find_sibling_pids()) {
+                unsafe {
+                    let _ = PostMessageW(Some(hwnd), WM_SYSCOMMAND, WPARAM(SC_RELOAD), LPARAM(0));
+                }
+            }
+            true
+        }
+        _ => false,
+    }
+}
 
-/// Add a custom "Tauri WebApp on Demand vX.Y.Z" item to the window's system menu
-/// and subclass the window to handle clicks on it.
-#[cfg(target_os = "windows")]
-fn setup_system_menu(window: &tauri::WebviewWindow) {
-    use windows::Win32::Foundation::HWND;
-    use windows::Win32::UI::WindowsAndMessaging::{
-        AppendMenuW, GetSystemMenu, MF_SEPARATOR, MF_STRING,
-    };
-    use windows::Win32::UI::Shell::SetWindowSubclass;
+#[cfg(not(target_os = "windows"))]
+fn handle_jump_list_task(_task: &str) -> bool {
+    false
+}
 
-    let Ok(hwnd) = window.hwnd() else { return };
-    let hwnd = HWND(hwnd.0 as *mut _);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
 
-    unsafe {
-        let hmenu = GetSystemMenu(hwnd, false);
-        if hmenu.is_invalid() {
-            return;
+    const DELAY: Duration = Duration::from_millis(500);
+
+    fn saved(width: u32, height: u32, scale_factor: f64) -> WindowState {
+        WindowState { width, height, scale_factor, ..Default::default() }
+    }
+
+    const AREA: Option<((i32, i32), (u32, u32))> = Some(((0, 0), (1920, 1040)));
+
+    #[test]
+    fn cascade_spacing_ignores_decorations() {
+        // A decorated and a frameless window, each restored by two instances from the
+        // same saved client position: the second instance's client area is one step on
+        let saved = (300, 200);
+        for (inset, frame) in [((8, 31), (16, 39)), ((0, 0), (0, 0))] {
+            let outer = (1024 + frame.0, 768 + frame.1);
+            let client = |(x, y): (i32, i32)| (x + inset.0, y + inset.1);
+            let first = restore_position(saved, inset, (0, 0), AREA, outer);
+            let second = restore_position(saved, inset, (32, 32), AREA, outer);
+            assert_eq!(client(first), saved);
+            assert_eq!(client(second), (saved.0 + 32, saved.1 + 32));
         }
+    }
 
-        // Add separator + about item
-        let _ = AppendMenuW(hmenu, MF_SEPARATOR, 0, None);
-        let label: Vec<u16> = format!("Tauri WebApp on Demand v{}", APP_VERSION)
-            .encode_utf16()
-            .chain(std::iter::once(0))
-            .collect();
-        let _ = AppendMenuW(
-            hmenu,
-            MF_STRING,
-            SC_ABOUT,
-            windows::core::PCWSTR(label.as_ptr()),
-        );
+    #[test]
+    fn cascade_wraps_using_the_restored_size() {
+        // Fits as a 1024 px wide window, but not as the 1424 px one it is restored to
+        let restored = (1424 + 16, 768 + 39);
+        assert_eq!(restore_position((460, 100), (8, 31), (32, 32), AREA, (1040, 807)).0, 484);
+        assert_eq!(restore_position((460, 100), (8, 31), (32, 32), AREA, restored).0, 4);
+    }
 
-        // Subclass to intercept WM_SYSCOMMAND for our custom menu item
-        let _ = SetWindowSubclass(hwnd, Some(sysmenu_subclass_proc), 1, 0);
+    #[test]
+    fn cascade_wraps_within_the_work_area() {
+        // 1920 px wide work area, 800 px window: top-left stays within 0..=1120
+        assert_eq!(wrap_into_area(1000, 0, 1920, 800), 1000);
+        assert_eq!(wrap_into_area(1152, 0, 1920, 800), 32);
+        assert_eq!(wrap_into_area(-32, 0, 1920, 800), 1088);
+        // Work area on a second monitor
+        assert_eq!(wrap_into_area(1920 + 1152, 1920, 1920, 800), 1920 + 32);
     }
-}
 
-#[cfg(target_os = "windows")]
-unsafe extern "system" fn sysmenu_subclass_proc(
-    hwnd: windows::Win32::Foundation::HWND,
-    umsg: u32,
-    wparam: windows::Win32::Foundation::WPARAM,
-    lparam: windows::Win32::Foundation::LPARAM,
-    _uidsubclass: usize,
-    _dwrefdata: usize,
-) -> windows::Win32::Foundation::LRESULT {
-    use windows::Win32::UI::Shell::DefSubclassProc;
-    use windows::Win32::UI::WindowsAndMessaging::WM_SYSCOMMAND;
+    #[test]
+    fn geometry_round_trips_without_creep() {
+        // Save, restore and save again with a decorated frame, over several launches
+        let (inset, frame) = ((8, 31), (16, 39));
+        let (x, y, width, height) = client_geometry((292, 169), (1040, 807), inset, frame);
+        let mut state = WindowState { x, y, ..saved(width, height, 1.5) };
+        for _ in 0..10 {
+            let (width, height) = restored_size(&state, Some(1.5), 200).unwrap();
+            let outer = (width + frame.0, height + frame.1);
+            let pos = restore_position((state.x, state.y), inset, (0, 0), AREA, outer);
+            let (x, y, width, height) = client_geometry(pos, outer, inset, frame);
+            state = WindowState { x, y, ..saved(width, height, 1.5) };
+        }
+        assert_eq!((state.x, state.y, state.width, state.height), (300, 200, 1024, 768));
+    }
 
-    if umsg == WM_SYSCOMMAND && wparam.0 == SC_ABOUT {
-        // Open the project URL in the default browser
-        use std::os::windows::process::CommandExt;
-        let _ = std::process::Command::new("cmd")
-            .args(["/C", "start", "", APP_REPO_URL])
-            .creation_flags(0x08000000) // CREATE_NO_WINDOW
-            .spawn();
-        return windows::Win32::Foundation::LRESULT(0);
+    #[test]
+    fn size_follows_a_dpi_change() {
+        assert_eq!(restored_size(&saved(1000, 800, 1.0), Some(1.5), 200), Some((1500, 1200)));
+        // Files from older versions have no scale factor
+        assert_eq!(restored_size(&saved(1000, 800, 0.0), Some(1.5), 200), Some((1000, 800)));
+        assert_eq!(restored_size(&saved(1000, 800, 1.0), None, 200), Some((1000, 800)));
     }
 
-    DefSubclassProc(hwnd, umsg, wparam, lparam)
-}
+    #[test]
+    fn tiny_saved_size_is_ignored() {
+        assert_eq!(restored_size(&saved(150, 800, 1.0), Some(1.0), 200), None);
+        assert_eq!(restored_size(&saved(150, 800, 1.0), Some(1.0), 100), Some((150, 800)));
+    }
 
-#[cfg(not(target_os = "windows"))]
-fn setup_system_menu(_window: &tauri::WebviewWindow) {
-    // System menu customization is Windows-only
+    #[test]
+    fn rapid_maximize_and_restore_is_not_saved() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut debounce = MaximizeDebounce::new();
+
+        // Snap Assist: maximized and restored again within the delay
+        assert_eq!(debounce.wait_left(true, at(0), DELAY), Some(DELAY));
+        assert_eq!(debounce.wait_left(false, at(100), DELAY), None);
+        // The delayed check then finds the window restored
+        assert_eq!(debounce.wait_left(false, at(500), DELAY), None);
+
+        // Maximizing again starts the wait over
+        assert_eq!(debounce.wait_left(true, at(600), DELAY), Some(DELAY));
+        assert_eq!(debounce.wait_left(false, at(700), DELAY), None);
+        assert_eq!(debounce.wait_left(true, at(800), DELAY), Some(DELAY));
+    }
+
+    #[test]
+    fn maximize_is_saved_once_it_lasts() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut debounce = MaximizeDebounce::new();
+
+        assert_eq!(debounce.wait_left(true, at(0), DELAY), Some(DELAY));
+        assert_eq!(debounce.wait_left(true, at(300), DELAY), Some(Duration::from_millis(200)));
+        assert_eq!(debounce.wait_left(true, at(500), DELAY), Some(Duration::ZERO));
+        assert_eq!(debounce.wait_left(true, at(900), DELAY), Some(Duration::ZERO));
+    }
 }
@@ -0,0 +1,32 @@
+use crate::config::AppConfig;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once from config at startup; logging is a no-op until enabled
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn init(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Append a line to `<exe_name>.log` beside the config (when logging is enabled).
+/// Each line is prefixed with the Unix timestamp in seconds.
+pub fn log(message: &str) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let Some(path) = AppConfig::log_path() else { return };
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+    {
+        let _ = writeln!(file, "[{}] {}", timestamp, message);
+    }
+}
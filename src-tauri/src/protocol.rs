@@ -0,0 +1,274 @@
+//! Custom URL protocol (`protocol_scheme`): registering the exe as the handler for
+//! `<scheme>://` links, and the WM_COPYDATA messages between instances: forwarding
+//! links (protocol or `--url`) and asking which page an instance shows.
+
+use crate::config::AppConfig;
+
+/// `COPYDATASTRUCT::dwData` tag for a URL forwarded to a running instance
+#[cfg(target_os = "windows")]
+pub const COPYDATA_NAVIGATE: usize = 0x5441_4e56; // "TANV"
+
+/// `COPYDATASTRUCT::dwData` tag asking a running instance whether it shows a URL
+#[cfg(target_os = "windows")]
+pub const COPYDATA_SHOWS_URL: usize = 0x5441_5355; // "TASU"
+
+/// URL of the page this instance currently shows (`single_window_per_url`)
+#[cfg(target_os = "windows")]
+static CURRENT_URL: std::sync::Mutex<String> = std::sync::Mutex::new(String::new());
+
+#[cfg(target_os = "windows")]
+pub fn set_current_url(url: &str) {
+    if let Ok(mut current) = CURRENT_URL.lock() {
+        *current = url.to_string();
+    }
+}
+
+/// Map a `<scheme>://<path>` command-line argument to a page on the configured site.
+/// The path is resolved relative to `url`; links that would leave its origin are
+/// ignored. Returns None for arguments that aren't links with our scheme.
+pub fn link_target(config: &AppConfig, scheme: &str, arg: &str) -> Option<tauri::Url> {
+    let (arg_scheme, rest) = arg.split_once(':')?;
+    if !arg_scheme.eq_ignore_ascii_case(scheme) {
+        return None;
+    }
+
+    let base: tauri::Url = config.url.parse().ok()?;
+    let target = base.join(rest.trim_start_matches('/')).ok()?;
+    if target.origin() != base.origin() {
+        crate::logging::log(&format!("Ignoring protocol link outside {}: {}", config.url, arg));
+        return None;
+    }
+    Some(target)
+}
+
+/// Whether a link forwarded by another instance may be opened: an http(s) page (all
+/// `--url` sends) or a page on the configured URL's origin (protocol links). Checked
+/// again on receipt, since any local process can send WM_COPYDATA.
+pub fn accepts_forwarded(config: &AppConfig, url: &tauri::Url) -> bool {
+    matches!(url.scheme(), "http" | "https") || config.is_own_origin(url.as_str())
+}
+
+/// Registry key (under HKCU) for the scheme's handler
+#[cfg(target_os = "windows")]
+fn class_key(scheme: &str) -> String {
+    format!("Software\\Classes\\{}", scheme)
+}
+
+/// Command line Windows runs for a link: `"<exe>" "%1"`
+#[cfg(target_os = "windows")]
+fn open_command() -> Option<String> {
+    let exe = std::env::current_exe().ok()?;
+    Some(format!("\"{}\" \"%1\"", exe.display()))
+}
+
+/// Register this exe as the handler for `<scheme>://` links for the current user.
+/// Unless `force` is set, nothing is written when the scheme already points at this exe.
+#[cfg(target_os = "windows")]
+pub fn register(scheme: &str, force: bool) {
+    use windows::core::HSTRING;
+    use windows::Win32::System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+
+    let Some(command) = open_command() else { return };
+    let key = class_key(scheme);
+    let command_key = format!("{}\\shell\\open\\command", key);
+
+    if !force && read_default_value(&command_key).as_deref() == Some(command.as_str()) {
+        return;
+    }
+
+    let set = |subkey: &str, name: Option<&str>, value: &str| unsafe {
+        let data: Vec<u16> = value.encode_utf16().chain(std::iter::once(0)).collect();
+        let name = name.map(HSTRING::from).unwrap_or_default();
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            &HSTRING::from(subkey),
+            &name,
+            REG_SZ.0,
+            Some(data.as_ptr() as *const _),
+            (data.len() * 2) as u32,
+        )
+        .is_ok()
+    };
+
+    let ok = set(&key, None, &format!("URL:{} Protocol", scheme))
+        && set(&key, Some("URL Protocol"), "")
+        && set(&command_key, None, &command);
+    crate::logging::log(&format!(
+        "{} {}:// protocol handler: {}",
+        if ok { "Registered" } else { "Failed to register" },
+        scheme,
+        command
+    ));
+}
+
+/// Remove the `<scheme>://` handler registration for the current user
+#[cfg(target_os = "windows")]
+pub fn unregister(scheme: &str) {
+    use windows::core::HSTRING;
+    use windows::Win32::System::Registry::{RegDeleteTreeW, HKEY_CURRENT_USER};
+
+    unsafe {
+        let _ = RegDeleteTreeW(HKEY_CURRENT_USER, &HSTRING::from(class_key(scheme)));
+    }
+    crate::logging::log(&format!("Unregistered {}:// protocol handler", scheme));
+}
+
+/// Default (unnamed) string value of an HKCU key
+#[cfg(target_os = "windows")]
+fn read_default_value(subkey: &str) -> Option<String> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_SZ};
+
+    let mut buffer = [0u16; 1024];
+    let mut size = (buffer.len() * 2) as u32;
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            &HSTRING::from(subkey),
+            PCWSTR::null(),
+            RRF_RT_REG_SZ,
+            None,
+            Some(buffer.as_mut_ptr() as *mut _),
+            Some(&mut size),
+        )
+        .ok()
+        .ok()?;
+    }
+    // `size` is in bytes and includes the terminating NUL
+    let len = (size as usize / 2).saturating_sub(1);
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
+
+/// Hand a link to a running instance (via WM_COPYDATA) and bring it to the front.
+/// Returns false when no other instance is running or it didn't take the link (its
+/// window isn't subclassed, see `subclass_features`, or it rejected the link).
+#[cfg(target_os = "windows")]
+pub fn forward_to_running_instance(url: &tauri::Url) -> bool {
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::System::DataExchange::COPYDATASTRUCT;
+    use windows::Win32::UI::WindowsAndMessaging::{SendMessageW, WM_COPYDATA};
+
+    let pids = crate::find_sibling_pids();
+    let Some(hwnd) = crate::find_process_window(&pids) else {
+        return false;
+    };
+
+    let data = url.as_str().as_bytes();
+    let copy = COPYDATASTRUCT {
+        dwData: COPYDATA_NAVIGATE,
+        cbData: data.len() as u32,
+        lpData: data.as_ptr() as *mut _,
+    };
+    let handled = unsafe {
+        SendMessageW(
+            hwnd,
+            WM_COPYDATA,
+            Some(WPARAM(0)),
+            Some(LPARAM(&copy as *const _ as isize)),
+        )
+    };
+    if handled.0 != 1 {
+        crate::logging::log(&format!("Running instance didn't take the link: {}", url));
+        return false;
+    }
+    crate::activate_process_windows(&pids);
+    true
+}
+
+/// Bring the running instance that currently shows `url` to the front.
+/// Returns false when no instance shows it (or none is running).
+#[cfg(target_os = "windows")]
+pub fn activate_instance_showing(url: &tauri::Url) -> bool {
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::System::DataExchange::COPYDATASTRUCT;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SendMessageTimeoutW, SMTO_ABORTIFHUNG, WM_COPYDATA,
+    };
+
+    let data = url.as_str().as_bytes();
+    let copy = COPYDATASTRUCT {
+        dwData: COPYDATA_SHOWS_URL,
+        cbData: data.len() as u32,
+        lpData: data.as_ptr() as *mut _,
+    };
+
+    for pid in crate::find_sibling_pids() {
+        let Some(hwnd) = crate::find_process_window(&[pid]) else {
+            continue;
+        };
+        let mut shows_url: usize = 0;
+        unsafe {
+            // A hung instance just doesn't count as a match
+            SendMessageTimeoutW(
+                hwnd,
+                WM_COPYDATA,
+                WPARAM(0),
+                LPARAM(&copy as *const _ as isize),
+                SMTO_ABORTIFHUNG,
+                1000,
+                Some(&mut shows_url),
+            );
+        }
+        if shows_url == 1 {
+            crate::activate_process_windows(&[pid]);
+            return true;
+        }
+    }
+    false
+}
+
+/// Answer a WM_COPYDATA message from another instance: Some(url) to navigate to for
+/// `forward_to_running_instance`, or whether this instance shows the URL asked about
+/// by `activate_instance_showing`. None for messages that aren't ours, and for links
+/// `accepts_forwarded` rejects.
+#[cfg(target_os = "windows")]
+pub unsafe fn handle_copy_data(
+    lparam: windows::Win32::Foundation::LPARAM,
+    config: &AppConfig,
+) -> Option<CopyData> {
+    use windows::Win32::System::DataExchange::COPYDATASTRUCT;
+
+    let copy = (lparam.0 as *const COPYDATASTRUCT).as_ref()?;
+    if copy.lpData.is_null() {
+        return None;
+    }
+    let data = std::slice::from_raw_parts(copy.lpData as *const u8, copy.cbData as usize);
+    let url: tauri::Url = std::str::from_utf8(data).ok()?.parse().ok()?;
+    match copy.dwData {
+        COPYDATA_NAVIGATE if accepts_forwarded(config, &url) => Some(CopyData::Navigate(url)),
+        COPYDATA_NAVIGATE => {
+            crate::logging::log(&format!("Ignoring forwarded link: {}", url));
+            None
+        }
+        COPYDATA_SHOWS_URL => {
+            let current = CURRENT_URL.lock().ok()?.parse::<tauri::Url>().ok();
+            Some(CopyData::ShowsUrl(current.as_ref() == Some(&url)))
+        }
+        _ => None,
+    }
+}
+
+/// A WM_COPYDATA request from another instance
+#[cfg(target_os = "windows")]
+pub enum CopyData {
+    Navigate(tauri::Url),
+    ShowsUrl(bool),
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn register(_scheme: &str, _force: bool) {
+    // Protocol registration uses the Windows registry; no-op on other platforms
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn unregister(_scheme: &str) {}
+
+#[cfg(not(target_os = "windows"))]
+pub fn forward_to_running_instance(_url: &tauri::Url) -> bool {
+    false
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn activate_instance_showing(_url: &tauri::Url) -> bool {
+    false
+}
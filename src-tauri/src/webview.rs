@@ -0,0 +1,1798 @@
+//! WebView2 event handlers and helpers used by `setup_webview_handlers` (Windows only)
+
+use crate::config::{AppConfig, LoadingTimeoutAction, Shortcut, ShortcutAction};
+use webview2_com::Microsoft::Web::WebView2::Win32::{ICoreWebView2, ICoreWebView2Controller};
+use windows::core::HSTRING;
+
+/// Apply config-driven `ICoreWebView2Settings` toggles
+pub unsafe fn apply_settings(core: &ICoreWebView2, config: &AppConfig) {
+    let Ok(settings) = core.Settings() else { return };
+
+    // Zoom lock: Ctrl+scroll, Ctrl+/- and pinch can't change the zoom level. Pinch is a
+    // separate setting from the other zoom controls.
+    if config.zoom_locked.eq_ignore_ascii_case("on") {
+        use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings5;
+        use windows::core::Interface;
+
+        let _ = settings.SetIsZoomControlEnabled(false);
+        if let Ok(settings) = settings.cast::<ICoreWebView2Settings5>() {
+            let _ = settings.SetIsPinchZoomEnabled(false);
+        }
+    }
+
+    // Let CSS `app-region: drag` areas through to the window (see `app_region`)
+    if config.app_region.eq_ignore_ascii_case("on") {
+        use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings9;
+        use windows::core::Interface;
+
+        if let Ok(settings) = settings.cast::<ICoreWebView2Settings9>() {
+            let _ = settings.SetIsNonClientRegionSupportEnabled(true);
+        }
+    }
+
+    // DevTools (F12, right-click > Inspect) in release builds, or off in debug builds
+    if !config.devtools.trim().is_empty() {
+        let _ = settings.SetAreDevToolsEnabled(config.devtools());
+    }
+
+    // No link-target URL popping up in the bottom corner on hover
+    if config.statusbar.eq_ignore_ascii_case("off") {
+        let _ = settings.SetIsStatusBarEnabled(false);
+    }
+
+    // No form autofill suggestions or "save password?" prompts (shared kiosks)
+    if config.autofill.eq_ignore_ascii_case("off") {
+        use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings4;
+        use windows::core::Interface;
+
+        if let Ok(settings) = settings.cast::<ICoreWebView2Settings4>() {
+            let _ = settings.SetIsGeneralAutofillEnabled(false);
+            let _ = settings.SetIsPasswordAutosaveEnabled(false);
+        }
+    }
+}
+
+/// Keyboard shortcuts handled by the wrapper rather than the page:
+/// - The `shortcuts` map from the config, checked first
+/// - F11 toggles fullscreen (when `fullscreen_key` is set). Leaving fullscreen returns
+///   the window to the size and position it had before (the saved window state isn't
+///   touched while fullscreen).
+/// - Ctrl+Shift+R / Ctrl+F5 reload bypassing the cache (see `hard_reload`)
+pub unsafe fn register_shortcut_keys(
+    controller: &ICoreWebView2Controller,
+    window: tauri::WebviewWindow,
+    fullscreen_key: bool,
+    shortcuts: Vec<Shortcut>,
+) {
+    use webview2_com::AcceleratorKeyPressedEventHandler;
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        COREWEBVIEW2_KEY_EVENT_KIND, COREWEBVIEW2_KEY_EVENT_KIND_KEY_DOWN,
+        COREWEBVIEW2_KEY_EVENT_KIND_SYSTEM_KEY_DOWN, COREWEBVIEW2_PHYSICAL_KEY_STATUS,
+    };
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        GetKeyState, VK_CONTROL, VK_F11, VK_F5, VK_MENU, VK_R, VK_SHIFT,
+    };
+
+    let Ok(core) = controller.CoreWebView2() else { return };
+    let zoom_controller = controller.clone();
+
+    let handler = AcceleratorKeyPressedEventHandler::create(Box::new(move |_controller, args| {
+        let Some(args) = args else {
+            return Ok(());
+        };
+
+        // Alt combinations arrive as system keys
+        let mut kind = COREWEBVIEW2_KEY_EVENT_KIND::default();
+        args.KeyEventKind(&mut kind)?;
+        if kind != COREWEBVIEW2_KEY_EVENT_KIND_KEY_DOWN
+            && kind != COREWEBVIEW2_KEY_EVENT_KIND_SYSTEM_KEY_DOWN
+        {
+            return Ok(());
+        }
+        let mut key: u32 = 0;
+        args.VirtualKey(&mut key)?;
+        let key = key as u16;
+        let ctrl = GetKeyState(VK_CONTROL.0 as i32) < 0;
+        let shift = GetKeyState(VK_SHIFT.0 as i32) < 0;
+        let alt = GetKeyState(VK_MENU.0 as i32) < 0;
+
+        let configured = shortcuts
+            .iter()
+            .find(|s| s.key == key && s.ctrl == ctrl && s.shift == shift && s.alt == alt)
+            .map(|s| s.action);
+        let action = configured.or(if fullscreen_key && key == VK_F11.0 {
+            Some(ShortcutAction::Fullscreen)
+        } else if ctrl && ((shift && key == VK_R.0) || key == VK_F5.0) {
+            Some(ShortcutAction::HardReload)
+        } else {
+            None
+        });
+        let Some(action) = action else {
+            return Ok(());
+        };
+        args.SetHandled(true)?;
+
+        // Ignore auto-repeat while the key is held down
+        let mut status = COREWEBVIEW2_PHYSICAL_KEY_STATUS::default();
+        args.PhysicalKeyStatus(&mut status)?;
+        if status.WasKeyDown.as_bool() {
+            return Ok(());
+        }
+
+        match action {
+            ShortcutAction::Reload => {
+                let _ = core.Reload();
+            }
+            ShortcutAction::HardReload => hard_reload(&core),
+            ShortcutAction::Home => {
+                use tauri::Manager;
+
+                // As the "Home page" menu item: forget the page resume_last_url would reopen
+                AppConfig::clear_last_url();
+                let url = &window.state::<AppConfig>().url;
+                let _ = core.Navigate(&HSTRING::from(url.as_str()));
+            }
+            ShortcutAction::ZoomIn | ShortcutAction::ZoomOut | ShortcutAction::ZoomReset => {
+                use tauri::Manager;
+
+                let config = window.state::<AppConfig>();
+                let zoom_locked = config.zoom_locked.eq_ignore_ascii_case("on");
+                if zoom_locked && action != ShortcutAction::ZoomReset {
+                    return Ok(());
+                }
+                let mut zoom = 1.0;
+                let _ = zoom_controller.ZoomFactor(&mut zoom);
+                let zoom = match action {
+                    ShortcutAction::ZoomIn => zoom * 1.1,
+                    ShortcutAction::ZoomOut => zoom / 1.1,
+                    // Back to default_zoom, which also drops a zoom_per_site override
+                    _ => config.default_zoom(),
+                };
+                let _ = zoom_controller.SetZoomFactor(zoom.clamp(0.25, 5.0));
+            }
+            ShortcutAction::Fullscreen => {
+                let is_fullscreen = window.is_fullscreen().unwrap_or(false);
+                let _ = window.set_fullscreen(!is_fullscreen);
+            }
+            // Through the normal close path, so the window state is saved
+            ShortcutAction::Quit => {
+                let _ = window.close();
+            }
+        }
+        Ok(())
+    }));
+
+    let mut token: i64 = 0;
+    let _ = controller.add_AcceleratorKeyPressed(&handler, &mut token);
+}
+
+/// Reload the page bypassing the HTTP cache, via the DevTools Protocol
+/// `Page.reload` with `ignoreCache: true` — for sites whose normal reload keeps
+/// serving stale cached assets.
+pub unsafe fn hard_reload(core: &ICoreWebView2) {
+    use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+
+    let callback =
+        CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_error, _result: String| {
+            Ok(())
+        }));
+    let _ = core.CallDevToolsProtocolMethod(
+        &HSTRING::from("Page.reload"),
+        &HSTRING::from(r#"{"ignoreCache":true}"#),
+        &callback,
+    );
+}
+
+/// Slow the page's scripts down by `rate` (1 = full speed) through the DevTools Protocol
+/// `Emulation.setCPUThrottlingRate`, for windows in the background (`throttle_background`)
+pub unsafe fn set_cpu_throttling(core: &ICoreWebView2, rate: f64) {
+    use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+
+    let callback =
+        CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_error, _result: String| {
+            Ok(())
+        }));
+    let _ = core.CallDevToolsProtocolMethod(
+        &HSTRING::from("Emulation.setCPUThrottlingRate"),
+        &HSTRING::from(serde_json::json!({ "rate": rate }).to_string()),
+        &callback,
+    );
+}
+
+/// Delete the cookies sent to the current page (its host's and parent domains') and
+/// reload it, for retrying a login flow without wiping all browsing data
+pub unsafe fn clear_site_cookies(core: &ICoreWebView2) {
+    use webview2_com::GetCookiesCompletedHandler;
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2_2;
+    use windows::core::Interface;
+
+    let source = current_source(core);
+    let Ok(manager) = core.cast::<ICoreWebView2_2>().and_then(|core| core.CookieManager())
+    else {
+        return;
+    };
+
+    let reload_core = core.clone();
+    let delete_manager = manager.clone();
+    let handler = GetCookiesCompletedHandler::create(Box::new(move |_error, list| {
+        let mut count = 0u32;
+        if let Some(list) = list {
+            list.Count(&mut count)?;
+            for index in 0..count {
+                if let Ok(cookie) = list.GetValueAtIndex(index) {
+                    let _ = delete_manager.DeleteCookie(&cookie);
+                }
+            }
+        }
+        crate::logging::log(&format!("Cleared {} cookies for the current site", count));
+        let _ = reload_core.Reload();
+        Ok(())
+    }));
+    let _ = manager.GetCookies(&HSTRING::from(source), &handler);
+}
+
+/// Run `script` in every document before the page's own scripts
+pub unsafe fn add_script(core: &ICoreWebView2, script: &str) {
+    use webview2_com::AddScriptToExecuteOnDocumentCreatedCompletedHandler;
+
+    let callback =
+        AddScriptToExecuteOnDocumentCreatedCompletedHandler::create(Box::new(|_error, _id| Ok(())));
+    let _ = core.AddScriptToExecuteOnDocumentCreated(&HSTRING::from(script), &callback);
+}
+
+/// Full-window spinner shown from the start of every page load until the page's
+/// `load` event or a `{ wrapper: "ready" }` window message (for apps that render
+/// later), with a safety limit (`maxMs`). Top-level document only. The logo and spinner
+/// are centered and sized relative to the window, so small windows aren't crowded.
+const LOADING_OVERLAY_SCRIPT: &str = r#"(() => {
+  if (window.top !== window) return;
+  const { color, logo, maxMs } = {options};
+  const overlay = document.createElement('div');
+  overlay.style.cssText = `position:fixed;inset:0;z-index:2147483647;display:flex;
+    flex-direction:column;align-items:center;justify-content:center;gap:clamp(12px,4vmin,32px);
+    background:${color};transition:opacity .2s`;
+  if (logo) {
+    const img = document.createElement('img');
+    img.src = logo;
+    img.style.cssText = 'max-width:min(40vw,320px);max-height:30vh;object-fit:contain';
+    overlay.appendChild(img);
+  }
+  const spinner = document.createElement('div');
+  spinner.style.cssText = `width:clamp(20px,5vmin,40px);aspect-ratio:1;border-radius:50%;
+    box-sizing:border-box;border:3px solid rgba(128,128,128,.25);
+    border-top-color:rgba(128,128,128,.8)`;
+  spinner.animate([{ transform: 'rotate(0)' }, { transform: 'rotate(360deg)' }],
+    { duration: 900, iterations: Infinity });
+  overlay.appendChild(spinner);
+
+  let hidden = false;
+  const hide = () => {
+    if (hidden) return;
+    hidden = true;
+    overlay.style.opacity = '0';
+    setTimeout(() => overlay.remove(), 250);
+  };
+  addEventListener('load', hide);
+  addEventListener('message', (e) => { if (e.data?.wrapper === 'ready') hide(); });
+  setTimeout(hide, maxMs);
+
+  // Injected before parsing starts, when there is no <html> element yet: attach as soon
+  // as the parser creates it
+  const attach = () => { if (!hidden) document.documentElement.appendChild(overlay); };
+  if (document.documentElement) {
+    attach();
+  } else {
+    const observer = new MutationObserver(() => {
+      if (!document.documentElement) return;
+      observer.disconnect();
+      attach();
+    });
+    observer.observe(document, { childList: true });
+  }
+})();"#;
+
+/// Show a loading spinner over the page while it loads (`loading_overlay`). With a
+/// navigation timeout, it stays up until the timeout action has taken over the page.
+pub unsafe fn register_loading_overlay(core: &ICoreWebView2, config: &AppConfig) {
+    let color = match config.loading_overlay_color.trim() {
+        "" if config.splash_bg().is_some() => config.splash_bg.trim(),
+        "" => "#1e1e1e",
+        color => color,
+    };
+    let max = config
+        .navigation_timeout()
+        .map_or(std::time::Duration::from_secs(30), |t| t + std::time::Duration::from_secs(1));
+    let options = serde_json::json!({
+        "color": color,
+        "logo": config.loading_overlay_logo.trim(),
+        "maxMs": max.as_millis() as u64,
+    });
+    add_script(core, &LOADING_OVERLAY_SCRIPT.replace("{options}", &options.to_string()));
+}
+
+/// Banner across the top of the page while the machine is offline (`navigator.onLine`),
+/// removed when the connection returns. The page stays as it is underneath. The close
+/// button dismisses it until the connection drops again. Top-level document only.
+const CONNECTION_BANNER_SCRIPT: &str = r#"(() => {
+  if (window.top !== window) return;
+  let banner = null;
+  const hide = () => { banner?.remove(); banner = null; };
+  const show = () => {
+    if (banner || !document.documentElement) return;
+    banner = document.createElement('div');
+    banner.setAttribute('role', 'alert');
+    banner.style.cssText = `position:fixed;top:0;left:0;right:0;z-index:2147483647;display:flex;
+      align-items:center;justify-content:center;gap:12px;padding:8px 40px;
+      background:#b3261e;color:#fff;font:14px/1.4 system-ui,sans-serif;
+      box-shadow:0 2px 6px rgba(0,0,0,.3)`;
+    banner.textContent = 'Connection lost — waiting for the network…';
+    const close = document.createElement('button');
+    close.textContent = '×';
+    close.title = 'Dismiss';
+    close.style.cssText = `position:absolute;right:8px;background:none;border:0;color:inherit;
+      font-size:20px;line-height:1;cursor:pointer`;
+    close.onclick = hide;
+    banner.appendChild(close);
+    document.documentElement.appendChild(banner);
+  };
+  addEventListener('offline', show);
+  addEventListener('online', hide);
+  if (!navigator.onLine) addEventListener('DOMContentLoaded', show);
+})();"#;
+
+/// Show a banner while offline instead of leaving the page to fail (`connection_banner`)
+pub unsafe fn register_connection_banner(core: &ICoreWebView2) {
+    add_script(core, CONNECTION_BANNER_SCRIPT);
+}
+
+/// Pauses media that starts playing without a recent click or key press, unless the
+/// frame's host is in `{hosts}` (patterns as in `host_matches`, without paths). Runs in
+/// every frame, so embedded third-party widgets are covered too.
+const AUTOPLAY_FILTER_SCRIPT: &str = r#"(() => {
+  const hosts = {hosts};
+  const host = location.hostname.toLowerCase();
+  const wildcard = (p) => new RegExp('^' + p.split('*').map(
+    (s) => s.replace(/[.+?^${}()|[\]\\]/g, '\\$&')).join('.*') + '$');
+  const allowed = hosts.some((p) => p.includes('*')
+    ? wildcard(p).test(host) : host === p || host.endsWith('.' + p));
+  if (allowed) return;
+  let gesture = 0;
+  const mark = () => { gesture = Date.now(); };
+  addEventListener('pointerdown', mark, true);
+  addEventListener('keydown', mark, true);
+  addEventListener('play', (e) => {
+    if (Date.now() - gesture > 1000 && e.target instanceof HTMLMediaElement) e.target.pause();
+  }, true);
+})();"#;
+
+/// Let only the `autoplay_hosts` autoplay media; media elsewhere needs a user gesture.
+/// The browser-wide `--autoplay-policy` (see `browser_args`) allows autoplay, and this
+/// script takes it back from the other hosts.
+pub unsafe fn register_autoplay_filter(core: &ICoreWebView2, hosts: &[String]) {
+    let hosts: Vec<String> = hosts.iter().map(|h| h.trim().to_ascii_lowercase()).collect();
+    let hosts = serde_json::to_string(&hosts).unwrap_or_else(|_| "[]".to_string());
+    add_script(core, &AUTOPLAY_FILTER_SCRIPT.replace("{hosts}", &hosts));
+}
+
+/// Reports `{locale}` as `navigator.language(s)` and makes it the default for `Intl`
+/// formatters and the `toLocale*String` methods, so locale-sensitive formatting in
+/// scripts matches the `--lang` the browser was started with. Explicit locales passed
+/// by the page are left alone.
+const LOCALE_SCRIPT: &str = r#"(() => {
+  const LOCALE = {locale};
+  Object.defineProperty(Navigator.prototype, 'language', { get: () => LOCALE });
+  Object.defineProperty(Navigator.prototype, 'languages', { get: () => [LOCALE] });
+  for (const name of ['DateTimeFormat', 'NumberFormat', 'Collator', 'PluralRules',
+    'RelativeTimeFormat', 'ListFormat', 'DisplayNames', 'Segmenter']) {
+    const Original = Intl[name];
+    if (!Original) continue;
+    const Wrapped = function (locales, options) {
+      return new.target
+        ? new Original(locales ?? LOCALE, options)
+        : Original(locales ?? LOCALE, options);
+    };
+    Wrapped.prototype = Original.prototype;
+    Wrapped.supportedLocalesOf = Original.supportedLocalesOf;
+    Intl[name] = Wrapped;
+  }
+  const withLocale = (proto, method) => {
+    const original = proto[method];
+    proto[method] = function (locales, ...rest) {
+      return original.call(this, locales ?? LOCALE, ...rest);
+    };
+  };
+  withLocale(Date.prototype, 'toLocaleString');
+  withLocale(Date.prototype, 'toLocaleDateString');
+  withLocale(Date.prototype, 'toLocaleTimeString');
+  withLocale(Number.prototype, 'toLocaleString');
+})();"#;
+
+/// Apply `locale` to scripts in every page
+pub unsafe fn register_locale_override(core: &ICoreWebView2, locale: &str) {
+    let locale = serde_json::Value::from(locale).to_string();
+    add_script(core, &LOCALE_SCRIPT.replace("{locale}", &locale));
+}
+
+/// Scales the root font size, so text sized in `rem`/`em` (most sites) grows without
+/// the layout zooming. `!important` wins over a page's own `html { font-size }`.
+const FONT_SCALE_SCRIPT: &str = r#"(() => {
+  const style = document.createElement('style');
+  style.textContent = 'html { font-size: {percent}% !important; }';
+  const attach = () => (document.head || document.documentElement).appendChild(style);
+  if (document.documentElement) attach();
+  else addEventListener('DOMContentLoaded', attach, { once: true });
+})();"#;
+
+/// Apply `font_scale` on every page, independently of the zoom level
+pub unsafe fn register_font_scale(core: &ICoreWebView2, scale: f64) {
+    let percent = format!("{:.1}", scale * 100.0);
+    add_script(core, &FONT_SCALE_SCRIPT.replace("{percent}", &percent));
+}
+
+/// Import cookies from a Netscape `cookies.txt` export (`cookies_file`), so a session
+/// logged in elsewhere carries over. Only cookies the configured site would send or
+/// set (its host, parent domains and subdomains) are imported; expired ones are
+/// skipped. Cookie values are credentials: only counts are logged.
+pub unsafe fn import_cookies(core: &ICoreWebView2, path: &std::path::Path, url: &tauri::Url) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2_2;
+    use windows::core::Interface;
+
+    let Some(host) = url.host_str() else { return };
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        crate::logging::log(&format!("Could not read cookies_file {}", path.display()));
+        return;
+    };
+    let Ok(manager) = core.cast::<ICoreWebView2_2>().and_then(|core| core.CookieManager())
+    else {
+        return;
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64())
+        .unwrap_or(0.0);
+
+    let (mut imported, mut skipped) = (0, 0);
+    for line in contents.lines() {
+        // curl and browser exporters mark HttpOnly cookies with this prefix
+        let (http_only, line) = match line.strip_prefix("#HttpOnly_") {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [domain, _include_subdomains, path, secure, expires, name, value] = fields[..] else {
+            skipped += 1;
+            continue;
+        };
+        // 0 marks a session cookie
+        let expires: f64 = expires.trim().parse().unwrap_or(0.0);
+        if (expires > 0.0 && expires < now) || !cookie_domain_matches(host, domain) {
+            skipped += 1;
+            continue;
+        }
+
+        let Ok(cookie) = manager.CreateCookie(
+            &HSTRING::from(name),
+            &HSTRING::from(value),
+            &HSTRING::from(domain),
+            &HSTRING::from(path),
+        ) else {
+            skipped += 1;
+            continue;
+        };
+        if expires > 0.0 {
+            let _ = cookie.SetExpires(expires);
+        }
+        let _ = cookie.SetIsSecure(secure.eq_ignore_ascii_case("TRUE"));
+        let _ = cookie.SetIsHttpOnly(http_only);
+        if manager.AddOrUpdateCookie(&cookie).is_ok() {
+            imported += 1;
+        } else {
+            skipped += 1;
+        }
+    }
+    crate::logging::log(&format!(
+        "Imported {} cookies from cookies_file ({} skipped)",
+        imported, skipped
+    ));
+}
+
+/// Whether a cookie for `domain` (leading dot optional) belongs to the site at `host`
+fn cookie_domain_matches(host: &str, domain: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+    host == domain
+        || host.ends_with(&format!(".{}", domain))
+        || domain.ends_with(&format!(".{}", host))
+}
+
+/// Sets the `local_storage` / `session_storage` entries from config on pages of the
+/// configured origin, before the page's scripts read them. Storage can throw (e.g.
+/// when disabled), which mustn't break the page.
+const STORAGE_SEED_SCRIPT: &str = r#"(() => {
+  const { origin, local, session } = {seed};
+  if (location.origin !== origin) return;
+  const seed = (storage, entries) => {
+    try {
+      for (const [key, value] of Object.entries(entries)) storage.setItem(key, value);
+    } catch (e) {}
+  };
+  seed(localStorage, local);
+  seed(sessionStorage, session);
+})();"#;
+
+/// Preseed localStorage/sessionStorage for `origin` (e.g. feature flags read by the app).
+/// Keys and values are opaque strings, embedded as one JSON literal (which is also a
+/// valid JS literal), so no value can break out of the script.
+pub unsafe fn register_storage_seed(core: &ICoreWebView2, origin: &str, config: &AppConfig) {
+    let seed = serde_json::json!({
+        "origin": origin,
+        "local": config.local_storage,
+        "session": config.session_storage,
+    });
+    add_script(core, &STORAGE_SEED_SCRIPT.replace("{seed}", &seed.to_string()));
+}
+
+/// Finds the `theme-color` meta that applies to the current color scheme and
+/// normalizes its value to `#rrggbb` through a canvas, so named and `rgb()` colors
+/// work too. Returns '' when there is none or it's translucent.
+const THEME_COLOR_SCRIPT: &str = r#"(() => {
+  const meta = [...document.querySelectorAll('meta[name="theme-color"]')]
+    .find(m => !m.media || matchMedia(m.media).matches);
+  if (!meta || !meta.content) return '';
+  const ctx = document.createElement('canvas').getContext('2d');
+  ctx.fillStyle = meta.content;
+  return ctx.fillStyle.startsWith('#') ? ctx.fillStyle : '';
+})()"#;
+
+/// Read the page's `<meta name="theme-color">` after every navigation and apply it
+/// as the window background and caption color.
+pub unsafe fn register_theme_color_sync(core: &ICoreWebView2, window: tauri::WebviewWindow) {
+    use webview2_com::{ExecuteScriptCompletedHandler, NavigationCompletedEventHandler};
+
+    let handler = NavigationCompletedEventHandler::create(Box::new(move |webview, _args| {
+        let Some(webview) = webview else {
+            return Ok(());
+        };
+
+        let window = window.clone();
+        let callback = ExecuteScriptCompletedHandler::create(Box::new(
+            move |_error, result: String| {
+                // The script result arrives JSON-encoded, i.e. as a quoted string
+                let color = serde_json::from_str::<String>(&result)
+                    .ok()
+                    .and_then(|c| parse_hex_color(&c));
+                if let Some(rgb) = color {
+                    let (r, g, b) = rgb;
+                    let _ = window.set_background_color(Some(tauri::window::Color(r, g, b, 255)));
+                    set_caption_color(&window, rgb);
+                }
+                Ok(())
+            },
+        ));
+        webview.ExecuteScript(&HSTRING::from(THEME_COLOR_SCRIPT), &callback)
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_NavigationCompleted(&handler, &mut token);
+}
+
+/// Parse `#rrggbb` into its components
+pub fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let component = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some((component(0)?, component(2)?, component(4)?))
+}
+
+/// Set the title bar color, with black or white caption text depending on the
+/// color's brightness. Requires Windows 11; silently ignored on Windows 10.
+pub fn set_caption_color(window: &tauri::WebviewWindow, (r, g, b): (u8, u8, u8)) {
+    use windows::Win32::Foundation::{COLORREF, HWND};
+    use windows::Win32::Graphics::Dwm::{
+        DwmSetWindowAttribute, DWMWA_CAPTION_COLOR, DWMWA_TEXT_COLOR,
+    };
+
+    let Ok(hwnd) = window.hwnd() else { return };
+    let hwnd = HWND(hwnd.0 as *mut _);
+
+    // COLORREF is 0x00BBGGRR
+    let caption = COLORREF((r as u32) | ((g as u32) << 8) | ((b as u32) << 16));
+    let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    let text = if luminance > 150.0 {
+        COLORREF(0x00000000)
+    } else {
+        COLORREF(0x00FFFFFF)
+    };
+
+    unsafe {
+        let _ = DwmSetWindowAttribute(
+            hwnd,
+            DWMWA_CAPTION_COLOR,
+            &caption as *const COLORREF as *const _,
+            std::mem::size_of::<COLORREF>() as u32,
+        );
+        let _ = DwmSetWindowAttribute(
+            hwnd,
+            DWMWA_TEXT_COLOR,
+            &text as *const COLORREF as *const _,
+            std::mem::size_of::<COLORREF>() as u32,
+        );
+    }
+}
+
+/// Friendly error page shown in place of a failed document. Placeholders are
+/// filled in by `show_error_page`.
+const ERROR_PAGE_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="UTF-8">
+<style>
+  body { margin: 0; background: #1a1a2e; color: #e0e0e0; font-family: system-ui, sans-serif;
+         display: flex; align-items: center; justify-content: center; height: 100vh; }
+  .box { text-align: center; max-width: 32rem; padding: 2rem; }
+  h1 { font-size: 1.6rem; font-weight: 600; margin: 0 0 0.75rem; }
+  p { opacity: 0.7; margin: 0 0 1.5rem; word-break: break-word; }
+  button { background: #3a3a5e; color: inherit; border: 0; border-radius: 6px;
+           padding: 0.6rem 1.6rem; font-size: 1rem; cursor: pointer; }
+  button:hover { background: #4a4a72; }
+</style>
+</head>
+<body>
+<div class="box">
+  <h1>{heading}</h1>
+  <p>{detail}</p>
+  <button onclick="location.href = retryUrl">Retry</button>
+</div>
+<script>const retryUrl = {retry_url};</script>
+</body>
+</html>"#;
+
+/// Replace the current document with the friendly error page. Its Retry button
+/// navigates to `retry_url`.
+pub unsafe fn show_error_page(core: &ICoreWebView2, heading: &str, detail: &str, retry_url: &str) {
+    // JSON string literal is a valid JS string; escape '<' so it can't close the <script>
+    let retry_url = serde_json::to_string(retry_url)
+        .unwrap_or_else(|_| "\"\"".to_string())
+        .replace('<', "\\u003c");
+    let html = ERROR_PAGE_HTML
+        .replace("{heading}", &html_escape(heading))
+        .replace("{detail}", &html_escape(detail))
+        .replace("{retry_url}", &retry_url);
+    let _ = core.NavigateToString(&HSTRING::from(html));
+}
+
+/// Show the error page when the main document comes back with an HTTP 4xx/5xx
+/// status, instead of rendering whatever body the server returned.
+pub unsafe fn register_http_error_page(core: &ICoreWebView2) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2NavigationCompletedEventArgs2;
+    use webview2_com::NavigationCompletedEventHandler;
+    use windows::core::Interface;
+
+    let handler = NavigationCompletedEventHandler::create(Box::new(move |webview, args| {
+        let (Some(webview), Some(args)) = (webview, args) else {
+            return Ok(());
+        };
+        // HttpStatusCode needs WebView2 runtime 1.0.1245+
+        let Ok(args) = args.cast::<ICoreWebView2NavigationCompletedEventArgs2>() else {
+            return Ok(());
+        };
+
+        let mut status: i32 = 0;
+        args.HttpStatusCode(&mut status)?;
+        if status >= 400 {
+            let url = current_source(&webview);
+            crate::logging::log(&format!("HTTP {} loading {}", status, url));
+            show_error_page(
+                &webview,
+                &format!("Error {} — {}", status, http_reason(status)),
+                if status >= 500 {
+                    "The server is having trouble right now. Try again in a moment."
+                } else {
+                    "The server could not provide this page."
+                },
+                &url,
+            );
+        }
+        Ok(())
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_NavigationCompleted(&handler, &mut token);
+}
+
+/// `--selftest`: print the outcome of the first navigation to a web page and exit —
+/// 0 when it loaded with a non-error status, 1 otherwise. The wrapper's own start page
+/// (`tauri.localhost`) and non-http(s) pages are skipped.
+pub unsafe fn register_selftest(core: &ICoreWebView2) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2NavigationCompletedEventArgs2, COREWEBVIEW2_WEB_ERROR_STATUS,
+    };
+    use webview2_com::NavigationCompletedEventHandler;
+    use windows::core::Interface;
+
+    let handler = NavigationCompletedEventHandler::create(Box::new(move |webview, args| {
+        let (Some(webview), Some(args)) = (webview, args) else {
+            return Ok(());
+        };
+        let url = current_source(&webview);
+        let Ok(parsed) = url.parse::<tauri::Url>() else { return Ok(()) };
+        if !matches!(parsed.scheme(), "http" | "https")
+            || parsed.host_str() == Some("tauri.localhost")
+        {
+            return Ok(());
+        }
+
+        let mut success = windows::core::BOOL::default();
+        args.IsSuccess(&mut success)?;
+        let mut status: i32 = 0;
+        if let Ok(args) = args.cast::<ICoreWebView2NavigationCompletedEventArgs2>() {
+            args.HttpStatusCode(&mut status)?;
+        }
+
+        let result = if success.as_bool() && status < 400 {
+            format!("SELFTEST OK {} (HTTP {})", url, status)
+        } else if status >= 400 {
+            format!("SELFTEST FAIL {} (HTTP {})", url, status)
+        } else {
+            let mut error = COREWEBVIEW2_WEB_ERROR_STATUS::default();
+            args.WebErrorStatus(&mut error)?;
+            format!("SELFTEST FAIL {} (network error {})", url, error.0)
+        };
+        crate::logging::log(&result);
+        println!("{}", result);
+        std::process::exit(if result.starts_with("SELFTEST OK") { 0 } else { 1 });
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_NavigationCompleted(&handler, &mut token);
+}
+
+/// `fallback_url`: when the first page load fails (network error or HTTP 5xx), retry it
+/// after 2, 4 and 8 seconds, then show `fallback` instead of an error page. Once a page
+/// has loaded, later failures are left to the usual error handling.
+pub unsafe fn register_fallback(
+    core: &ICoreWebView2,
+    window: tauri::WebviewWindow,
+    fallback: tauri::Url,
+) {
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::Arc;
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2NavigationCompletedEventArgs2, COREWEBVIEW2_WEB_ERROR_STATUS,
+        COREWEBVIEW2_WEB_ERROR_STATUS_OPERATION_CANCELED,
+    };
+    use webview2_com::NavigationCompletedEventHandler;
+    use windows::core::Interface;
+
+    const RETRIES: u32 = 3;
+    let attempts = Arc::new(AtomicU32::new(0));
+    let settled = Arc::new(AtomicBool::new(false));
+
+    let handler = NavigationCompletedEventHandler::create(Box::new(move |webview, args| {
+        let (Some(webview), Some(args)) = (webview, args) else {
+            return Ok(());
+        };
+        if settled.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        // The wrapper's own start page and error pages aren't the site
+        let url = current_source(&webview);
+        let Ok(parsed) = url.parse::<tauri::Url>() else { return Ok(()) };
+        if !matches!(parsed.scheme(), "http" | "https")
+            || parsed.host_str() == Some("tauri.localhost")
+        {
+            return Ok(());
+        }
+
+        let mut success = windows::core::BOOL::default();
+        args.IsSuccess(&mut success)?;
+        let mut error = COREWEBVIEW2_WEB_ERROR_STATUS::default();
+        args.WebErrorStatus(&mut error)?;
+        let mut status: i32 = 0;
+        if let Ok(args) = args.cast::<ICoreWebView2NavigationCompletedEventArgs2>() {
+            args.HttpStatusCode(&mut status)?;
+        }
+        // Replaced by another navigation (e.g. a redirect): wait for that one
+        if !success.as_bool() && error == COREWEBVIEW2_WEB_ERROR_STATUS_OPERATION_CANCELED {
+            return Ok(());
+        }
+        if success.as_bool() && status < 500 {
+            settled.store(true, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        let attempt = attempts.fetch_add(1, Ordering::Relaxed) + 1;
+        if attempt > RETRIES {
+            settled.store(true, Ordering::Relaxed);
+            crate::logging::log(&format!("{} is unreachable, showing {}", url, fallback));
+            let _ = webview.Navigate(&HSTRING::from(fallback.as_str()));
+            return Ok(());
+        }
+
+        crate::logging::log(&format!("Loading {} failed, retry {} of {}", url, attempt, RETRIES));
+        let window = window.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_secs(1 << attempt));
+            let _ = window.with_webview(move |webview| unsafe {
+                if let Ok(core) = webview.controller().CoreWebView2() {
+                    let _ = core.Navigate(&HSTRING::from(url));
+                }
+            });
+        });
+        Ok(())
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_NavigationCompleted(&handler, &mut token);
+}
+
+/// The failover target currently in use (`urls`), for the diagnostics overlay: its
+/// position in the list and its URL, or the fallback page. None without failover.
+pub static FAILOVER_TARGET: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+/// How often a failed-over window checks whether the primary target is back
+const PRIMARY_RECHECK: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Failover for `urls`: when a page on one target fails to load (network error or
+/// HTTP 5xx), go to the next target. Once every target has failed in a row, show
+/// `fallback` if set, else start over with the first one after a pause. While a
+/// backup (or the fallback) is showing, the primary is probed every `PRIMARY_RECHECK`
+/// and the window goes back to it as soon as its server accepts connections.
+pub unsafe fn register_failover(
+    core: &ICoreWebView2,
+    window: tauri::WebviewWindow,
+    targets: Vec<tauri::Url>,
+    fallback: Option<tauri::Url>,
+) {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2NavigationCompletedEventArgs2, COREWEBVIEW2_WEB_ERROR_STATUS,
+        COREWEBVIEW2_WEB_ERROR_STATUS_OPERATION_CANCELED,
+    };
+    use webview2_com::NavigationCompletedEventHandler;
+    use windows::core::Interface;
+
+    const FALLBACK: usize = usize::MAX;
+    let targets = Arc::new(targets);
+    let active = Arc::new(AtomicUsize::new(0));
+    let failures = Arc::new(AtomicUsize::new(0));
+    set_failover_target(&targets, 0);
+
+    let navigate = |window: &tauri::WebviewWindow, url: String, delay: std::time::Duration| {
+        let window = window.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            let _ = window.with_webview(move |webview| unsafe {
+                if let Ok(core) = webview.controller().CoreWebView2() {
+                    let _ = core.Navigate(&HSTRING::from(url));
+                }
+            });
+        });
+    };
+
+    let (state_targets, state_active) = (targets.clone(), active.clone());
+    let handler_window = window.clone();
+    let handler = NavigationCompletedEventHandler::create(Box::new(move |webview, args| {
+        let (Some(webview), Some(args)) = (webview, args) else {
+            return Ok(());
+        };
+        // Only pages of the targets count; other sites the user opens are left alone
+        let url = current_source(&webview);
+        let Ok(parsed) = url.parse::<tauri::Url>() else { return Ok(()) };
+        let Some(index) = state_targets.iter().position(|t| t.origin() == parsed.origin())
+        else {
+            return Ok(());
+        };
+
+        let mut success = windows::core::BOOL::default();
+        args.IsSuccess(&mut success)?;
+        let mut error = COREWEBVIEW2_WEB_ERROR_STATUS::default();
+        args.WebErrorStatus(&mut error)?;
+        let mut status: i32 = 0;
+        if let Ok(args) = args.cast::<ICoreWebView2NavigationCompletedEventArgs2>() {
+            args.HttpStatusCode(&mut status)?;
+        }
+        // Replaced by another navigation (e.g. a redirect): wait for that one
+        if !success.as_bool() && error == COREWEBVIEW2_WEB_ERROR_STATUS_OPERATION_CANCELED {
+            return Ok(());
+        }
+        if success.as_bool() && status < 500 {
+            failures.store(0, Ordering::Relaxed);
+            if state_active.swap(index, Ordering::Relaxed) != index {
+                crate::logging::log(&format!("Failover: now on {}", state_targets[index]));
+                set_failover_target(&state_targets, index);
+            }
+            return Ok(());
+        }
+
+        if failures.fetch_add(1, Ordering::Relaxed) + 1 >= state_targets.len() {
+            failures.store(0, Ordering::Relaxed);
+            if let Some(fallback) = &fallback {
+                crate::logging::log(&format!("Failover: all targets down, showing {}", fallback));
+                state_active.store(FALLBACK, Ordering::Relaxed);
+                if let Ok(mut target) = FAILOVER_TARGET.lock() {
+                    *target = Some(format!("fallback ({})", fallback));
+                }
+                let _ = webview.Navigate(&HSTRING::from(fallback.as_str()));
+            } else {
+                crate::logging::log("Failover: all targets down, starting over in 30 s");
+                let pause = std::time::Duration::from_secs(30);
+                navigate(&handler_window, state_targets[0].to_string(), pause);
+            }
+            return Ok(());
+        }
+
+        let next = (index + 1) % state_targets.len();
+        crate::logging::log(&format!("Failover: {} failed, trying {}", url, state_targets[next]));
+        navigate(&handler_window, state_targets[next].to_string(), std::time::Duration::ZERO);
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_NavigationCompleted(&handler, &mut token);
+
+    // Promote back to the primary once its server answers again
+    std::thread::spawn(move || loop {
+        std::thread::sleep(PRIMARY_RECHECK);
+        if active.load(Ordering::Relaxed) != 0 && server_reachable(&targets[0]) {
+            crate::logging::log(&format!("Failover: {} is back", targets[0]));
+            navigate(&window, targets[0].to_string(), std::time::Duration::ZERO);
+        }
+    });
+}
+
+fn set_failover_target(targets: &[tauri::Url], index: usize) {
+    if let Ok(mut target) = FAILOVER_TARGET.lock() {
+        *target = Some(format!("{} of {} ({})", index + 1, targets.len(), targets[index]));
+    }
+}
+
+/// Whether the server of `url` accepts a TCP connection within 5 seconds
+fn server_reachable(url: &tauri::Url) -> bool {
+    use std::net::TcpStream;
+
+    let Ok(addresses) = url.socket_addrs(|| None) else { return false };
+    addresses.iter().any(|address| {
+        TcpStream::connect_timeout(address, std::time::Duration::from_secs(5)).is_ok()
+    })
+}
+
+/// Total working set, in MB, of the WebView2 processes (browser, renderers, GPU, ...)
+/// serving this webview. None when the runtime is too old to list them.
+pub unsafe fn memory_usage_mb(core: &ICoreWebView2) -> Option<u64> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2Environment8, ICoreWebView2_2,
+    };
+    use windows::core::Interface;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    let environment = core.cast::<ICoreWebView2_2>().ok()?.Environment().ok()?;
+    let infos = environment.cast::<ICoreWebView2Environment8>().ok()?.GetProcessInfos().ok()?;
+    let mut count: u32 = 0;
+    infos.Count(&mut count).ok()?;
+
+    let mut bytes: u64 = 0;
+    for index in 0..count {
+        let mut pid: i32 = 0;
+        let Ok(info) = infos.GetValueAtIndex(index) else { continue };
+        if info.ProcessId(&mut pid).is_err() {
+            continue;
+        }
+        let Ok(process) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid as u32) else {
+            continue;
+        };
+        let mut counters = PROCESS_MEMORY_COUNTERS::default();
+        let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        if GetProcessMemoryInfo(process, &mut counters, size).is_ok() {
+            bytes += counters.WorkingSetSize as u64;
+        }
+        let _ = CloseHandle(process);
+    }
+    Some(bytes / (1024 * 1024))
+}
+
+/// Remember the top-level URL on every `SourceChanged` so the next launch can resume
+/// there. Only http(s) pages are stored — error pages and `about:` URLs are skipped.
+pub unsafe fn register_last_url_tracking(core: &ICoreWebView2) {
+    use webview2_com::SourceChangedEventHandler;
+
+    let handler = SourceChangedEventHandler::create(Box::new(move |webview, _args| {
+        let Some(webview) = webview else {
+            return Ok(());
+        };
+        let url = current_source(&webview);
+        let lower = url.to_ascii_lowercase();
+        if lower.starts_with("http://") || lower.starts_with("https://") {
+            AppConfig::save_last_url(&url);
+        }
+        Ok(())
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_SourceChanged(&handler, &mut token);
+}
+
+/// Add every top-level http(s) page to the recent pages list (`recent_pages`)
+pub unsafe fn register_history_tracking(core: &ICoreWebView2) {
+    use webview2_com::SourceChangedEventHandler;
+
+    let handler = SourceChangedEventHandler::create(Box::new(move |webview, _args| {
+        let Some(webview) = webview else {
+            return Ok(());
+        };
+        let url = current_source(&webview);
+        let lower = url.to_ascii_lowercase();
+        if lower.starts_with("http://") || lower.starts_with("https://") {
+            AppConfig::record_history(&url);
+        }
+        Ok(())
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_SourceChanged(&handler, &mut token);
+}
+
+/// Browser-like zoom per site (`zoom_per_site`): a zoom change is remembered for the
+/// current page's origin, and every page opens at its origin's saved zoom, or at
+/// `default_zoom` for origins without one. Saved across restarts.
+pub unsafe fn register_site_zoom(
+    controller: &ICoreWebView2Controller,
+    core: &ICoreWebView2,
+    default_zoom: f64,
+) {
+    use std::sync::{Arc, Mutex};
+    use webview2_com::{SourceChangedEventHandler, ZoomFactorChangedEventHandler};
+
+    let site_zoom = Arc::new(Mutex::new(AppConfig::load_site_zoom()));
+    let origin_of = |core: &ICoreWebView2| {
+        let url = current_source(core).parse::<tauri::Url>().ok()?;
+        matches!(url.scheme(), "http" | "https").then(|| url.origin().ascii_serialization())
+    };
+
+    let zoom_controller = controller.clone();
+    let saved = site_zoom.clone();
+    let handler = SourceChangedEventHandler::create(Box::new(move |webview, _args| {
+        let Some(origin) = webview.as_ref().and_then(origin_of) else {
+            return Ok(());
+        };
+        let zoom = saved.lock().ok().and_then(|z| z.get(&origin).copied());
+        let zoom = zoom.unwrap_or(default_zoom);
+        let mut current = 1.0;
+        let _ = zoom_controller.ZoomFactor(&mut current);
+        if (current - zoom).abs() > 0.001 {
+            let _ = zoom_controller.SetZoomFactor(zoom);
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_SourceChanged(&handler, &mut token);
+
+    let source_core = core.clone();
+    let handler = ZoomFactorChangedEventHandler::create(Box::new(move |controller, _args| {
+        let (Some(controller), Some(origin)) = (controller, origin_of(&source_core)) else {
+            return Ok(());
+        };
+        let mut zoom = 1.0;
+        controller.ZoomFactor(&mut zoom)?;
+        let Ok(mut saved) = site_zoom.lock() else { return Ok(()) };
+        let changed = if (zoom - default_zoom).abs() < 0.001 {
+            saved.remove(&origin).is_some()
+        } else {
+            saved.insert(origin, zoom).map_or(true, |previous| (previous - zoom).abs() > 0.001)
+        };
+        if changed {
+            AppConfig::save_site_zoom(&saved);
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = controller.add_ZoomFactorChanged(&handler, &mut token);
+}
+
+/// Hand links with one of `schemes` (e.g. `mailto:`, `tel:`) to the system shell,
+/// both for in-page navigations and for links that would open a new window.
+pub unsafe fn register_external_schemes(core: &ICoreWebView2, schemes: Vec<String>) {
+    use webview2_com::{NavigationStartingEventHandler, NewWindowRequestedEventHandler};
+
+    let schemes = std::sync::Arc::new(schemes);
+
+    let navigation_schemes = schemes.clone();
+    let handler = NavigationStartingEventHandler::create(Box::new(move |_webview, args| {
+        let Some(args) = args else {
+            return Ok(());
+        };
+        let mut uri = windows::core::PWSTR::null();
+        args.Uri(&mut uri)?;
+        let uri = webview2_com::take_pwstr(uri);
+        if has_scheme(&uri, &navigation_schemes) {
+            args.SetCancel(true)?;
+            shell_open(&uri);
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_NavigationStarting(&handler, &mut token);
+
+    let handler = NewWindowRequestedEventHandler::create(Box::new(move |_webview, args| {
+        let Some(args) = args else {
+            return Ok(());
+        };
+        let mut uri = windows::core::PWSTR::null();
+        args.Uri(&mut uri)?;
+        let uri = webview2_com::take_pwstr(uri);
+        if has_scheme(&uri, &schemes) {
+            args.SetHandled(true)?;
+            shell_open(&uri);
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_NewWindowRequested(&handler, &mut token);
+}
+
+/// Whether `uri` starts with `<scheme>:` for one of the (lowercase) `schemes`
+fn has_scheme(uri: &str, schemes: &[String]) -> bool {
+    uri.split_once(':')
+        .is_some_and(|(scheme, _)| schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme)))
+}
+
+/// Open a URI with its registered handler (mail client, dialer, ...)
+fn shell_open(uri: &str) {
+    use windows::core::w;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    crate::logging::log(&format!("Opening externally: {}", uri));
+    unsafe {
+        let _ = ShellExecuteW(None, w!("open"), &HSTRING::from(uri), None, None, SW_SHOWNORMAL);
+    }
+}
+
+/// Keep the shared current-URL record up to date for `single_window_per_url`
+pub unsafe fn register_current_url_tracking(core: &ICoreWebView2) {
+    use webview2_com::SourceChangedEventHandler;
+
+    let handler = SourceChangedEventHandler::create(Box::new(move |webview, _args| {
+        if let Some(webview) = webview {
+            crate::protocol::set_current_url(&current_source(&webview));
+        }
+        Ok(())
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_SourceChanged(&handler, &mut token);
+}
+
+/// URL of the current top-level document
+pub unsafe fn current_source(core: &ICoreWebView2) -> String {
+    let mut uri = windows::core::PWSTR::null();
+    if core.Source(&mut uri).is_err() {
+        return String::new();
+    }
+    webview2_com::take_pwstr(uri)
+}
+
+/// Reason phrase for common HTTP status codes
+fn http_reason(status: i32) -> &'static str {
+    match status {
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        408 => "Request Timeout",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        s if s >= 500 => "Server Error",
+        _ => "Client Error",
+    }
+}
+
+/// Escape text for inclusion in HTML content
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Stop a navigation that hasn't completed within `timeout` (e.g. the server accepted
+/// the connection but never responded), then show the error page with a Retry button,
+/// load the page again or show the fallback page, depending on `action`.
+pub unsafe fn register_navigation_timeout(
+    core: &ICoreWebView2,
+    window: tauri::WebviewWindow,
+    timeout: std::time::Duration,
+    action: LoadingTimeoutAction,
+) {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use webview2_com::{NavigationCompletedEventHandler, NavigationStartingEventHandler};
+
+    // ID of the navigation currently in flight (0 = none)
+    let pending = Arc::new(AtomicU64::new(0));
+
+    let pending_start = pending.clone();
+    let starting = NavigationStartingEventHandler::create(Box::new(move |_webview, args| {
+        let Some(args) = args else {
+            return Ok(());
+        };
+        let mut id: u64 = 0;
+        args.NavigationId(&mut id)?;
+        let mut uri = windows::core::PWSTR::null();
+        args.Uri(&mut uri)?;
+        let uri = webview2_com::take_pwstr(uri);
+        pending_start.store(id, Ordering::SeqCst);
+
+        let pending = pending_start.clone();
+        let window = window.clone();
+        let action = action.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(timeout);
+            if pending.load(Ordering::SeqCst) != id {
+                return;
+            }
+            let _ = window.with_webview(move |webview| unsafe {
+                // Re-check on the UI thread — it may have completed in the meantime
+                if pending.compare_exchange(id, 0, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+                    return;
+                }
+                let Ok(core) = webview.controller().CoreWebView2() else {
+                    return;
+                };
+                crate::logging::log(&format!("Navigation to {} timed out", uri));
+                let _ = core.Stop();
+                match &action {
+                    LoadingTimeoutAction::ShowError => show_error_page(
+                        &core,
+                        "Page load timed out",
+                        "The server did not respond in time.",
+                        &uri,
+                    ),
+                    LoadingTimeoutAction::Retry => {
+                        let _ = core.Navigate(&HSTRING::from(uri.as_str()));
+                    }
+                    LoadingTimeoutAction::Fallback(fallback) => {
+                        let _ = core.Navigate(&HSTRING::from(fallback.as_str()));
+                    }
+                }
+            });
+        });
+        Ok(())
+    }));
+
+    let completed = NavigationCompletedEventHandler::create(Box::new(move |_webview, args| {
+        if let Some(args) = args {
+            let mut id: u64 = 0;
+            args.NavigationId(&mut id)?;
+            let _ = pending.compare_exchange(id, 0, Ordering::SeqCst, Ordering::SeqCst);
+        }
+        Ok(())
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_NavigationStarting(&starting, &mut token);
+    let _ = core.add_NavigationCompleted(&completed, &mut token);
+}
+
+/// Enforce `min_tls` = 1.3: watch document responses through the DevTools Protocol
+/// (`Network.responseReceived`) and replace the page with the error page when the
+/// connection negotiated an older TLS version. WebView2 has no switch to raise the
+/// minimum itself; it already refuses anything below TLS 1.2.
+pub unsafe fn register_min_tls_check(core: &ICoreWebView2, min_minor: u8) {
+    use webview2_com::{
+        CallDevToolsProtocolMethodCompletedHandler, DevToolsProtocolEventReceivedEventHandler,
+    };
+
+    let event = HSTRING::from("Network.responseReceived");
+    let Ok(receiver) = core.GetDevToolsProtocolEventReceiver(&event) else { return };
+
+    let handler = DevToolsProtocolEventReceivedEventHandler::create(Box::new(
+        move |webview, args| {
+            let (Some(webview), Some(args)) = (webview, args) else {
+                return Ok(());
+            };
+            let mut json = windows::core::PWSTR::null();
+            args.ParameterObjectAsJson(&mut json)?;
+            let json = webview2_com::take_pwstr(json);
+            let Ok(params) = serde_json::from_str::<serde_json::Value>(&json) else {
+                return Ok(());
+            };
+            if params["type"] != "Document" {
+                return Ok(());
+            }
+
+            let response = &params["response"];
+            let Some(protocol) = response["securityDetails"]["protocol"].as_str() else {
+                // Plain http or no TLS details
+                return Ok(());
+            };
+            let minor = match protocol {
+                "QUIC" => 3,
+                p => p
+                    .strip_prefix("TLS 1.")
+                    .and_then(|m| m.parse::<u8>().ok())
+                    .unwrap_or(0),
+            };
+            if minor < min_minor {
+                let url = response["url"].as_str().unwrap_or_default();
+                crate::logging::log(&format!(
+                    "{} used {}, below min_tls 1.{}",
+                    url, protocol, min_minor
+                ));
+                let _ = webview.Stop();
+                show_error_page(
+                    &webview,
+                    "Connection not secure enough",
+                    &format!(
+                        "The site negotiated {}, but this app requires TLS 1.{} or newer.",
+                        protocol, min_minor
+                    ),
+                    url,
+                );
+            }
+            Ok(())
+        },
+    ));
+
+    let mut token: i64 = 0;
+    let _ = receiver.add_DevToolsProtocolEventReceived(&handler, &mut token);
+
+    let callback =
+        CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_error, _result: String| {
+            Ok(())
+        }));
+    let _ = core.CallDevToolsProtocolMethod(
+        &HSTRING::from("Network.enable"),
+        &HSTRING::from("{}"),
+        &callback,
+    );
+}
+
+/// Clear the profile's browsing data of the given kinds (`cookies`, `cache`, `storage`,
+/// `cache_storage`, `service_workers`, `history`, `downloads`, `autofill`, `passwords`,
+/// `all`), then run `done`, also when nothing could be cleared. Unknown kinds are
+/// ignored. Needs WebView2 runtime 1.0.1245+.
+pub unsafe fn clear_browsing_data(
+    core: &ICoreWebView2,
+    kinds: &[String],
+    done: impl FnOnce() + 'static,
+) {
+    use webview2_com::ClearBrowsingDataCompletedHandler;
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2Profile2, ICoreWebView2_13, COREWEBVIEW2_BROWSING_DATA_KINDS,
+        COREWEBVIEW2_BROWSING_DATA_KINDS_ALL_DOM_STORAGE,
+        COREWEBVIEW2_BROWSING_DATA_KINDS_ALL_PROFILE,
+        COREWEBVIEW2_BROWSING_DATA_KINDS_BROWSING_HISTORY,
+        COREWEBVIEW2_BROWSING_DATA_KINDS_CACHE_STORAGE,
+        COREWEBVIEW2_BROWSING_DATA_KINDS_COOKIES, COREWEBVIEW2_BROWSING_DATA_KINDS_DISK_CACHE,
+        COREWEBVIEW2_BROWSING_DATA_KINDS_DOWNLOAD_HISTORY,
+        COREWEBVIEW2_BROWSING_DATA_KINDS_GENERAL_AUTOFILL,
+        COREWEBVIEW2_BROWSING_DATA_KINDS_PASSWORD_AUTOSAVE,
+        COREWEBVIEW2_BROWSING_DATA_KINDS_SERVICE_WORKERS,
+    };
+    use windows::core::Interface;
+
+    let mut mask = 0;
+    for kind in kinds {
+        mask |= match kind.as_str() {
+            "cookies" => COREWEBVIEW2_BROWSING_DATA_KINDS_COOKIES.0,
+            "cache" => COREWEBVIEW2_BROWSING_DATA_KINDS_DISK_CACHE.0,
+            "storage" => COREWEBVIEW2_BROWSING_DATA_KINDS_ALL_DOM_STORAGE.0,
+            "cache_storage" => COREWEBVIEW2_BROWSING_DATA_KINDS_CACHE_STORAGE.0,
+            "service_workers" => COREWEBVIEW2_BROWSING_DATA_KINDS_SERVICE_WORKERS.0,
+            "history" => COREWEBVIEW2_BROWSING_DATA_KINDS_BROWSING_HISTORY.0,
+            "downloads" => COREWEBVIEW2_BROWSING_DATA_KINDS_DOWNLOAD_HISTORY.0,
+            "autofill" => COREWEBVIEW2_BROWSING_DATA_KINDS_GENERAL_AUTOFILL.0,
+            "passwords" => COREWEBVIEW2_BROWSING_DATA_KINDS_PASSWORD_AUTOSAVE.0,
+            "all" => COREWEBVIEW2_BROWSING_DATA_KINDS_ALL_PROFILE.0,
+            _ => 0,
+        };
+    }
+    if mask == 0 {
+        done();
+        return;
+    }
+
+    let Ok(profile) = core
+        .cast::<ICoreWebView2_13>()
+        .and_then(|core13| core13.Profile())
+        .and_then(|profile| profile.cast::<ICoreWebView2Profile2>())
+    else {
+        crate::logging::log("Clearing browsing data needs a newer WebView2 runtime");
+        done();
+        return;
+    };
+
+    let handler = ClearBrowsingDataCompletedHandler::create(Box::new(move |_result| {
+        done();
+        Ok(())
+    }));
+    let _ = profile.ClearBrowsingData(COREWEBVIEW2_BROWSING_DATA_KINDS(mask), &handler);
+}
+
+/// Empty response with the given status (e.g. 204) to answer a blocked request with
+unsafe fn empty_response(
+    core: &ICoreWebView2,
+    status: i32,
+    reason: &str,
+) -> Option<webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2WebResourceResponse> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2_2;
+    use windows::core::Interface;
+    use windows::Win32::System::Com::IStream;
+
+    let environment = core.cast::<ICoreWebView2_2>().ok()?.Environment().ok()?;
+    environment
+        .CreateWebResourceResponse(
+            None::<&IStream>,
+            status,
+            &HSTRING::from(reason),
+            &HSTRING::new(),
+        )
+        .ok()
+}
+
+/// `script_dialogs: off`: `alert`, `confirm` and `prompt` are dismissed without being
+/// shown (`confirm` returns false, `prompt` null), and "leave this page?" prompts from
+/// `beforeunload` are answered with leave, so nothing can block an unattended page
+pub unsafe fn register_script_dialog_suppression(core: &ICoreWebView2) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        COREWEBVIEW2_SCRIPT_DIALOG_KIND, COREWEBVIEW2_SCRIPT_DIALOG_KIND_BEFOREUNLOAD,
+    };
+    use webview2_com::ScriptDialogOpeningEventHandler;
+
+    if let Ok(settings) = core.Settings() {
+        let _ = settings.SetAreDefaultScriptDialogsEnabled(false);
+    }
+
+    let handler = ScriptDialogOpeningEventHandler::create(Box::new(|_webview, args| {
+        let Some(args) = args else { return Ok(()) };
+        let mut kind = COREWEBVIEW2_SCRIPT_DIALOG_KIND::default();
+        args.Kind(&mut kind)?;
+        if kind == COREWEBVIEW2_SCRIPT_DIALOG_KIND_BEFOREUNLOAD {
+            args.Accept()?;
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_ScriptDialogOpening(&handler, &mut token);
+}
+
+/// `downloads: off`: cancel every download before it starts
+pub unsafe fn register_download_blocking(core: &ICoreWebView2) {
+    use webview2_com::DownloadStartingEventHandler;
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2_4;
+    use windows::core::Interface;
+
+    let Ok(core) = core.cast::<ICoreWebView2_4>() else { return };
+    let handler = DownloadStartingEventHandler::create(Box::new(|_webview, args| {
+        if let Some(args) = args {
+            args.SetCancel(true)?;
+            args.SetHandled(true)?;
+            crate::logging::log("Blocked a download");
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_DownloadStarting(&handler, &mut token);
+}
+
+/// Handle the page calling `window.close()`. The window closes through the normal close
+/// path, so its state is saved. With `hide`, it is hidden instead — to the tray when there
+/// is one (`start_hidden`), otherwise minimized so it can't get lost.
+/// `tray_taskbar: keep` minimizes rather than hides, keeping the taskbar button.
+pub unsafe fn register_close_request(
+    core: &ICoreWebView2,
+    window: tauri::WebviewWindow,
+    hide: bool,
+) {
+    use tauri::Manager;
+    use webview2_com::WindowCloseRequestedEventHandler;
+
+    let handler = WindowCloseRequestedEventHandler::create(Box::new(move |_webview, _args| {
+        if !hide {
+            let _ = window.close();
+        } else if window.state::<AppConfig>().start_hidden.eq_ignore_ascii_case("on") {
+            crate::hide_to_tray(&window);
+        } else {
+            let _ = window.minimize();
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_WindowCloseRequested(&handler, &mut token);
+}
+
+/// `permission_prompts: off`: deny every permission request (notifications, camera,
+/// location, ...) without showing WebView2's prompt
+pub unsafe fn register_permission_denial(core: &ICoreWebView2) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::COREWEBVIEW2_PERMISSION_STATE_DENY;
+    use webview2_com::PermissionRequestedEventHandler;
+
+    let handler = PermissionRequestedEventHandler::create(Box::new(|_webview, args| {
+        if let Some(args) = args {
+            args.SetState(COREWEBVIEW2_PERMISSION_STATE_DENY)?;
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_PermissionRequested(&handler, &mut token);
+}
+
+/// Text-only mode (`load_images: off`): answer every image request with an empty
+/// 204 response, so images are never downloaded.
+pub unsafe fn register_image_blocking(core: &ICoreWebView2) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT, COREWEBVIEW2_WEB_RESOURCE_CONTEXT_IMAGE,
+    };
+    use webview2_com::WebResourceRequestedEventHandler;
+
+    let _ = core.AddWebResourceRequestedFilter(
+        &HSTRING::from("*"),
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_IMAGE,
+    );
+
+    let handler = WebResourceRequestedEventHandler::create(Box::new(move |webview, args| {
+        let (Some(webview), Some(args)) = (webview, args) else {
+            return Ok(());
+        };
+        // Other handlers may have added filters for other resource types
+        let mut context = COREWEBVIEW2_WEB_RESOURCE_CONTEXT::default();
+        args.ResourceContext(&mut context)?;
+        if context != COREWEBVIEW2_WEB_RESOURCE_CONTEXT_IMAGE {
+            return Ok(());
+        }
+        if let Some(response) = empty_response(&webview, 204, "No Content") {
+            args.SetResponse(&response)?;
+        }
+        Ok(())
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_WebResourceRequested(&handler, &mut token);
+}
+
+/// Lightweight ad/tracker blocker: requests whose host matches one of `patterns` (see
+/// `host_matches`) get an empty 403 response. The number of blocked requests per page
+/// load is logged.
+pub unsafe fn register_host_blocking(core: &ICoreWebView2, patterns: Vec<String>) {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use webview2_com::Microsoft::Web::WebView2::Win32::COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL;
+    use webview2_com::{NavigationCompletedEventHandler, WebResourceRequestedEventHandler};
+
+    let _ = core.AddWebResourceRequestedFilter(
+        &HSTRING::from("*"),
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL,
+    );
+
+    let patterns: Vec<String> = patterns.iter().map(|p| p.trim().to_ascii_lowercase()).collect();
+    let blocked = Arc::new(AtomicUsize::new(0));
+
+    let blocked_count = blocked.clone();
+    let handler = WebResourceRequestedEventHandler::create(Box::new(move |webview, args| {
+        let (Some(webview), Some(args)) = (webview, args) else {
+            return Ok(());
+        };
+        let mut uri = windows::core::PWSTR::null();
+        args.Request()?.Uri(&mut uri)?;
+        let Ok(url) = webview2_com::take_pwstr(uri).parse::<tauri::Url>() else {
+            return Ok(());
+        };
+        let Some(host) = url.host_str() else {
+            return Ok(());
+        };
+        let host = host.to_ascii_lowercase();
+        if patterns.iter().any(|pattern| host_matches(pattern, &host, url.path())) {
+            blocked_count.fetch_add(1, Ordering::Relaxed);
+            if let Some(response) = empty_response(&webview, 403, "Blocked") {
+                args.SetResponse(&response)?;
+            }
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_WebResourceRequested(&handler, &mut token);
+
+    let handler = NavigationCompletedEventHandler::create(Box::new(move |webview, _args| {
+        let count = blocked.swap(0, Ordering::Relaxed);
+        if count > 0 {
+            let url = webview.map(|w| current_source(&w)).unwrap_or_default();
+            crate::logging::log(&format!("Blocked {} requests on {}", count, url));
+        }
+        Ok(())
+    }));
+    let mut token: i64 = 0;
+    let _ = core.add_NavigationCompleted(&handler, &mut token);
+}
+
+/// Whether a request to `host` (lowercase) and `path` matches a host pattern (lowercase).
+/// Without `/`, the pattern matches the host and its subdomains (`example.com`) or is a
+/// `*` wildcard (`ads.*.com`); with a `/` it is matched against `host/path`.
+fn host_matches(pattern: &str, host: &str, path: &str) -> bool {
+    if pattern.contains('/') {
+        wildcard_match(pattern, &format!("{}{}", host, path))
+    } else if pattern.contains('*') {
+        wildcard_match(pattern, host)
+    } else {
+        host == pattern || host.ends_with(&format!(".{}", pattern))
+    }
+}
+
+/// Send a fixed Referer header on requests to the configured destination hosts
+/// (`referer`: host pattern → referer, patterns as in `host_matches`), for embedded
+/// widgets that check it. Other requests keep their normal referer.
+pub unsafe fn register_referer_override(core: &ICoreWebView2, referers: Vec<(String, String)>) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL;
+    use webview2_com::WebResourceRequestedEventHandler;
+
+    let _ = core.AddWebResourceRequestedFilter(
+        &HSTRING::from("*"),
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL,
+    );
+
+    let referers: Vec<(String, String)> = referers
+        .into_iter()
+        .map(|(pattern, referer)| (pattern.trim().to_ascii_lowercase(), referer))
+        .collect();
+
+    let handler = WebResourceRequestedEventHandler::create(Box::new(move |_webview, args| {
+        let Some(args) = args else {
+            return Ok(());
+        };
+        let request = args.Request()?;
+        let mut uri = windows::core::PWSTR::null();
+        request.Uri(&mut uri)?;
+        let Ok(url) = webview2_com::take_pwstr(uri).parse::<tauri::Url>() else {
+            return Ok(());
+        };
+        let Some(host) = url.host_str() else {
+            return Ok(());
+        };
+        let host = host.to_ascii_lowercase();
+
+        let referer = referers
+            .iter()
+            .find(|(pattern, _)| host_matches(pattern, &host, url.path()));
+        if let Some((_, referer)) = referer {
+            request
+                .Headers()?
+                .SetHeader(&HSTRING::from("Referer"), &HSTRING::from(referer.as_str()))?;
+        }
+        Ok(())
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_WebResourceRequested(&handler, &mut token);
+}
+
+/// Present requests to the configured site as addressed to `host` (`host_header`):
+/// rewrite their Host header, and their Origin header when there is one. Only requests
+/// to `target` (the configured URL's scheme, host and port) are touched, so third-party
+/// requests are sent as usual.
+pub unsafe fn register_host_header(core: &ICoreWebView2, target: tauri::Url, host: String) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL;
+    use webview2_com::WebResourceRequestedEventHandler;
+
+    let _ = core.AddWebResourceRequestedFilter(
+        &HSTRING::from("*"),
+        COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL,
+    );
+    let origin = format!("{}://{}", target.scheme(), host);
+
+    let handler = WebResourceRequestedEventHandler::create(Box::new(move |_webview, args| {
+        let Some(args) = args else {
+            return Ok(());
+        };
+        let request = args.Request()?;
+        let mut uri = windows::core::PWSTR::null();
+        request.Uri(&mut uri)?;
+        let Ok(url) = webview2_com::take_pwstr(uri).parse::<tauri::Url>() else {
+            return Ok(());
+        };
+        if url.origin() != target.origin() {
+            return Ok(());
+        }
+
+        let headers = request.Headers()?;
+        headers.SetHeader(&HSTRING::from("Host"), &HSTRING::from(host.as_str()))?;
+        let mut has_origin = windows::core::BOOL::default();
+        headers.Contains(&HSTRING::from("Origin"), &mut has_origin)?;
+        if has_origin.as_bool() {
+            headers.SetHeader(&HSTRING::from("Origin"), &HSTRING::from(origin.as_str()))?;
+        }
+        Ok(())
+    }));
+
+    let mut token: i64 = 0;
+    let _ = core.add_WebResourceRequested(&handler, &mut token);
+}
+
+/// Glob match where `*` stands for any run of characters (including none)
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No '*' at all: exact match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Mirror the Windows accessibility settings into the page's media features via the
+/// DevTools Protocol (`Emulation.setEmulatedMedia`): "Animation effects" off →
+/// `prefers-reduced-motion: reduce`; a high-contrast theme → `prefers-contrast: more`
+/// and `forced-colors: active`. Call again when the settings change.
+pub unsafe fn apply_accessibility_media(core: &ICoreWebView2) {
+    use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+    use windows::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION, SPI_GETHIGHCONTRAST,
+        SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+    };
+
+    let mut animations = windows::core::BOOL(1);
+    let _ = SystemParametersInfoW(
+        SPI_GETCLIENTAREAANIMATION,
+        0,
+        Some(&mut animations as *mut _ as *mut _),
+        SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+    );
+    let mut contrast = HIGHCONTRASTW {
+        cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+        ..Default::default()
+    };
+    let _ = SystemParametersInfoW(
+        SPI_GETHIGHCONTRAST,
+        contrast.cbSize,
+        Some(&mut contrast as *mut _ as *mut _),
+        SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+    );
+    let reduce_motion = !animations.as_bool();
+    let high_contrast = contrast.dwFlags.0 & HCF_HIGHCONTRASTON.0 != 0;
+
+    let params = serde_json::json!({
+        "features": [
+            {
+                "name": "prefers-reduced-motion",
+                "value": if reduce_motion { "reduce" } else { "no-preference" },
+            },
+            {
+                "name": "prefers-contrast",
+                "value": if high_contrast { "more" } else { "no-preference" },
+            },
+            {
+                "name": "forced-colors",
+                "value": if high_contrast { "active" } else { "none" },
+            },
+        ]
+    });
+    let callback =
+        CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_error, _result: String| {
+            Ok(())
+        }));
+    let _ = core.CallDevToolsProtocolMethod(
+        &HSTRING::from("Emulation.setEmulatedMedia"),
+        &HSTRING::from(params.to_string()),
+        &callback,
+    );
+}